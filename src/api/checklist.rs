@@ -0,0 +1,355 @@
+//! Checklists are stored by the Vikunja editor as task lists inside the
+//! description HTML:
+//!
+//! ```html
+//! <ul data-type="taskList">
+//!   <li data-checked="true" data-type="taskItem"><label><input type="checkbox" checked="checked"><span></span></label><div><p>item</p></div></li>
+//! </ul>
+//! ```
+
+pub struct ChecklistItem {
+    pub checked: bool,
+    pub text: String,
+    /// Byte range of the opening `<li ...>` tag in the description
+    tag: std::ops::Range<usize>,
+}
+
+/// Position of the end of the tag starting at `start`
+fn tag_end(html: &str, start: usize) -> Option<usize> {
+    let mut quote = None;
+
+    for (i, c) in html[start..].char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('>', None) => return Some(start + i + 1),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let start = tag.find(&format!(" {name}=\""))? + name.len() + 3;
+    let end = tag[start..].find('"')? + start;
+    Some(&tag[start..end])
+}
+
+fn is_tag(html: &str, pos: usize, name: &str) -> bool {
+    html[pos..].starts_with(&format!("<{name}"))
+        && html[pos + name.len() + 1..]
+            .chars()
+            .next()
+            .is_some_and(|c| c == '>' || c == '/' || c.is_whitespace())
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+fn encode_entities(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Text of a list item up to its nested lists, without tags
+fn item_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut pos = 0;
+
+    while pos < html.len() {
+        if html[pos..].starts_with('<') {
+            if is_tag(html, pos, "ul") || is_tag(html, pos, "ol") {
+                break;
+            }
+            pos = tag_end(html, pos).unwrap_or(html.len());
+            text.push(' ');
+        } else {
+            let next = html[pos..].find('<').map_or(html.len(), |x| x + pos);
+            text.push_str(&html[pos..next]);
+            pos = next;
+        }
+    }
+
+    decode_entities(&text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// All checklist items in document order, including nested ones
+pub fn parse(html: &str) -> Vec<ChecklistItem> {
+    let mut items = Vec::new();
+    let mut pos = 0;
+
+    while let Some(found) = html[pos..].find("<li") {
+        let start = pos + found;
+        let Some(end) = tag_end(html, start) else {
+            break;
+        };
+        pos = end;
+
+        let tag = &html[start..end];
+        if !is_tag(html, start, "li") || attribute(tag, "data-type") != Some("taskItem") {
+            continue;
+        }
+
+        let content_end = html[end..].find("</li>").map_or(html.len(), |x| x + end);
+
+        items.push(ChecklistItem {
+            checked: attribute(tag, "data-checked") == Some("true"),
+            text: item_text(&html[end..content_end]),
+            tag: start..end,
+        });
+    }
+
+    items
+}
+
+/// Done and total amount of checklist items, if there are any
+pub fn progress(html: &str) -> Option<(usize, usize)> {
+    let items = parse(html);
+
+    if items.is_empty() {
+        return None;
+    }
+
+    Some((items.iter().filter(|x| x.checked).count(), items.len()))
+}
+
+/// Set the checked state of the `n`th (1-based) item
+pub fn set_checked(html: &str, n: usize, checked: bool) -> Result<String, String> {
+    let items = parse(html);
+
+    let item = n.checked_sub(1).and_then(|i| items.get(i)).map_or_else(
+        || {
+            Err(format!(
+                "There is no checklist item {n}, the task has {}",
+                items.len()
+            ))
+        },
+        Ok,
+    )?;
+
+    let tag = &html[item.tag.clone()];
+    let value = if checked { "true" } else { "false" };
+    let new_tag = if attribute(tag, "data-checked").is_some() {
+        tag.replacen(
+            &format!(" data-checked=\"{}\"", !checked),
+            &format!(" data-checked=\"{value}\""),
+            1,
+        )
+    } else {
+        tag.replacen("<li", &format!("<li data-checked=\"{value}\""), 1)
+    };
+
+    // Keep the checkbox input in sync for renderers ignoring `data-checked`
+    let content_start = item.tag.end;
+    let content = &html[content_start..];
+    let content = match content
+        .find("<input")
+        .and_then(|x| Some((x, tag_end(content, x)?)))
+    {
+        Some((start, end)) if content[..start].find("</li>").is_none() => {
+            let input = content[start..end]
+                .replace(" checked=\"checked\"", "")
+                .replace(" checked", "");
+            let input = if checked {
+                input.replacen("<input", "<input checked=\"checked\"", 1)
+            } else {
+                input
+            };
+            format!("{}{input}{}", &content[..start], &content[end..])
+        }
+        _ => content.to_string(),
+    };
+
+    Ok(format!("{}{new_tag}{content}", &html[..item.tag.start]))
+}
+
+/// Append an unchecked item to the last checklist or start a new one
+pub fn append(html: &str, text: &str) -> String {
+    let item = format!(
+        "<li data-checked=\"false\" data-type=\"taskItem\"><label><input type=\"checkbox\"><span></span></label><div><p>{}</p></div></li>",
+        encode_entities(text)
+    );
+
+    // The last top level task list and the position of its closing tag
+    let mut depth = 0;
+    let mut last_list = None;
+    let mut open_list = None;
+    let mut pos = 0;
+
+    while pos < html.len() {
+        if is_tag(html, pos, "ul") {
+            if depth == 0 {
+                open_list = tag_end(html, pos)
+                    .filter(|end| attribute(&html[pos..*end], "data-type") == Some("taskList"))
+                    .map(|_| pos);
+            }
+            depth += 1;
+        } else if html[pos..].starts_with("</ul>") {
+            depth -= 1;
+            if depth == 0 && open_list.is_some() {
+                last_list = Some(pos);
+            }
+        }
+        pos += html[pos..].chars().next().map_or(1, char::len_utf8);
+    }
+
+    if let Some(close) = last_list {
+        return format!("{}{item}{}", &html[..close], &html[close..]);
+    }
+
+    let html = if html == "<p></p>" { "" } else { html };
+    format!("{html}<ul data-type=\"taskList\">{item}</ul>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(checked: bool, text: &str) -> String {
+        format!(
+            "<li data-checked=\"{checked}\" data-type=\"taskItem\"><label><input type=\"checkbox\"{}><span></span></label><div><p>{text}</p></div></li>",
+            if checked { " checked=\"checked\"" } else { "" }
+        )
+    }
+
+    fn list(items: &[String]) -> String {
+        format!("<ul data-type=\"taskList\">{}</ul>", items.concat())
+    }
+
+    fn states(html: &str) -> Vec<(bool, String)> {
+        parse(html)
+            .into_iter()
+            .map(|x| (x.checked, x.text))
+            .collect()
+    }
+
+    #[test]
+    fn parse_items() {
+        let html = format!(
+            "<p>Intro</p>{}",
+            list(&[item(true, "first"), item(false, "a &amp; b")])
+        );
+
+        assert_eq!(
+            states(&html),
+            [(true, "first".to_string()), (false, "a & b".to_string())]
+        );
+        assert_eq!(progress(&html), Some((1, 2)));
+    }
+
+    #[test]
+    fn no_checklist() {
+        let html = "<p>Just text</p><ul><li>bullet</li></ul>";
+
+        assert!(parse(html).is_empty());
+        assert_eq!(progress(html), None);
+        assert_eq!(progress(""), None);
+        assert!(set_checked(html, 1, true).is_err());
+    }
+
+    #[test]
+    fn check_item() {
+        let html = list(&[item(false, "first"), item(false, "second")]);
+
+        let checked = set_checked(&html, 2, true).unwrap();
+        assert_eq!(
+            states(&checked),
+            [(false, "first".to_string()), (true, "second".to_string())]
+        );
+        assert!(checked.contains("<input checked=\"checked\" type=\"checkbox\">"));
+        assert_eq!(checked.matches("checked=\"checked\"").count(), 1);
+    }
+
+    #[test]
+    fn uncheck_item() {
+        let html = list(&[item(true, "first"), item(true, "second")]);
+
+        let unchecked = set_checked(&html, 1, false).unwrap();
+        assert_eq!(
+            states(&unchecked),
+            [(false, "first".to_string()), (true, "second".to_string())]
+        );
+        assert_eq!(unchecked.matches("checked=\"checked\"").count(), 1);
+
+        // Toggling back restores the original state
+        assert_eq!(
+            states(&set_checked(&unchecked, 1, true).unwrap()),
+            states(&html)
+        );
+    }
+
+    #[test]
+    fn check_missing_item() {
+        let html = list(&[item(false, "only")]);
+
+        assert!(set_checked(&html, 0, true).is_err());
+        assert!(set_checked(&html, 2, true).is_err());
+    }
+
+    #[test]
+    fn nested_items() {
+        let nested = format!(
+            "<li data-checked=\"false\" data-type=\"taskItem\"><label><input type=\"checkbox\"><span></span></label><div><p>parent</p>{}</div></li>",
+            list(&[item(false, "child")])
+        );
+        let html = list(&[nested, item(false, "last")]);
+
+        assert_eq!(
+            states(&html),
+            [
+                (false, "parent".to_string()),
+                (false, "child".to_string()),
+                (false, "last".to_string())
+            ]
+        );
+
+        let checked = set_checked(&html, 2, true).unwrap();
+        assert_eq!(
+            states(&checked),
+            [
+                (false, "parent".to_string()),
+                (true, "child".to_string()),
+                (false, "last".to_string())
+            ]
+        );
+
+        // New items go to the end of the top level list, not the nested one
+        let appended = append(&html, "new");
+        assert_eq!(
+            states(&appended).last().unwrap(),
+            &(false, "new".to_string())
+        );
+        assert!(appended.ends_with(&format!("{}</ul>", item(false, "new"))));
+    }
+
+    #[test]
+    fn append_to_checklist() {
+        let html = format!("{}<p>Notes</p>", list(&[item(true, "first")]));
+
+        let appended = append(&html, "a < b");
+        assert_eq!(
+            states(&appended),
+            [(true, "first".to_string()), (false, "a < b".to_string())]
+        );
+        assert!(appended.contains("<p>a &lt; b</p>"));
+        assert!(appended.ends_with("</ul><p>Notes</p>"));
+    }
+
+    #[test]
+    fn append_without_checklist() {
+        assert_eq!(append("<p></p>", "first"), list(&[item(false, "first")]));
+        assert_eq!(
+            append("<p>Text</p>", "first"),
+            format!("<p>Text</p>{}", list(&[item(false, "first")]))
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+use super::User;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedFilter {
+    pub id: isize,
+    pub title: String,
+    pub description: String,
+    pub filters: FilterQuery,
+    pub owner: Option<User>,
+    pub is_favorite: bool,
+    pub created: String,
+    pub updated: String,
+}
+
+/// Task query of a saved filter.
+///
+/// Newer Vikunja versions use the `filter` query string, older ones the
+/// `filter_by` / `filter_value` / `filter_comparator` lists. Both are sent so
+/// either server understands the filter.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FilterQuery {
+    #[serde(default)]
+    pub filter: Option<String>,
+    #[serde(default)]
+    pub filter_by: Option<Vec<String>>,
+    #[serde(default)]
+    pub filter_value: Option<Vec<String>>,
+    #[serde(default)]
+    pub filter_comparator: Option<Vec<String>>,
+    #[serde(default)]
+    pub filter_concat: Option<String>,
+    #[serde(default)]
+    pub filter_include_nulls: bool,
+}
+
+impl FilterQuery {
+    /// Build a query from the flags of the default listing command
+    pub fn new(done: bool, project: Option<isize>, label: Option<usize>) -> Self {
+        let mut conditions: Vec<(&str, &str, &str, String)> = Vec::new();
+
+        if !done {
+            conditions.push(("done", "done", "equals", "false".to_string()));
+        }
+
+        if let Some(project) = project {
+            conditions.push(("project", "project_id", "equals", project.to_string()));
+        }
+
+        if let Some(label) = label {
+            conditions.push(("labels", "labels", "in", label.to_string()));
+        }
+
+        let filter = conditions
+            .iter()
+            .map(|(field, _, comparator, value)| {
+                let op = if *comparator == "in" { "in" } else { "=" };
+                format!("{field} {op} {value}")
+            })
+            .collect::<Vec<_>>()
+            .join(" && ");
+
+        Self {
+            filter: Some(filter),
+            filter_by: Some(conditions.iter().map(|x| x.1.to_string()).collect()),
+            filter_value: Some(conditions.iter().map(|x| x.3.clone()).collect()),
+            filter_comparator: Some(conditions.iter().map(|x| x.2.to_string()).collect()),
+            filter_concat: Some("and".to_string()),
+            filter_include_nulls: false,
+        }
+    }
+
+    /// Human readable representation of the query
+    pub fn describe(&self) -> String {
+        if let Some(filter) = self.filter.as_ref().filter(|x| !x.is_empty()) {
+            return filter.clone();
+        }
+
+        let by = self.filter_by.clone().unwrap_or_default();
+        let value = self.filter_value.clone().unwrap_or_default();
+        let comparator = self.filter_comparator.clone().unwrap_or_default();
+
+        by.iter()
+            .zip(value.iter())
+            .enumerate()
+            .map(|(i, (by, value))| {
+                format!(
+                    "{by} {} {value}",
+                    comparator.get(i).map_or("equals", String::as_str)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(&format!(
+                " {} ",
+                self.filter_concat.as_deref().unwrap_or("and")
+            ))
+    }
+}
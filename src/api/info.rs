@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+/// Server information from `/info`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Info {
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub frontend_url: String,
+    #[serde(default)]
+    pub motd: String,
+    #[serde(default = "enabled")]
+    pub link_sharing_enabled: bool,
+    #[serde(default = "enabled")]
+    pub task_comments_enabled: bool,
+}
+
+const fn enabled() -> bool {
+    true
+}
+
+/// Features which not every Vikunja server has
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    /// Projects replaced lists in 0.21
+    Projects,
+    /// Setting `bucket_id` on tasks, replaced by project views in 0.24
+    TaskBuckets,
+    LinkShares,
+    Comments,
+}
+
+impl Feature {
+    pub fn repr(self) -> String {
+        match self {
+            Self::Projects => "projects",
+            Self::TaskBuckets => "moving tasks into buckets",
+            Self::LinkShares => "link shares",
+            Self::Comments => "task comments",
+        }
+        .to_string()
+    }
+}
+
+impl Info {
+    /// `(major, minor, patch)` of versions like `v0.22.1` or `v0.24.0-123-gabcdef`.
+    ///
+    /// Development builds without a version number are `None`.
+    pub fn version(&self) -> Option<(u32, u32, u32)> {
+        let version = self.version.trim_start_matches('v');
+        let version = version.split(['-', '+']).next()?;
+
+        let mut parts = version.split('.').map(str::parse::<u32>);
+        Some((
+            parts.next()?.ok()?,
+            parts.next().unwrap_or(Ok(0)).ok()?,
+            parts.next().unwrap_or(Ok(0)).ok()?,
+        ))
+    }
+
+    /// Whether the server supports `feature`. Unknown versions count as the newest.
+    pub fn supports(&self, feature: Feature) -> bool {
+        let version = self.version();
+
+        match feature {
+            Feature::Projects => version.is_none_or(|x| x >= (0, 21, 0)),
+            Feature::TaskBuckets => version.is_some_and(|x| x < (0, 24, 0)),
+            Feature::LinkShares => self.link_sharing_enabled,
+            Feature::Comments => self.task_comments_enabled,
+        }
+    }
+}
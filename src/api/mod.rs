@@ -1,14 +1,31 @@
 use serde::{Deserialize, Serialize};
 
+pub mod checklist;
+mod filter;
+mod info;
+pub mod progress;
 mod project;
+mod resolve;
+mod select;
+mod share;
 mod task;
-
-pub use project::Project;
+mod team;
+mod token;
+
+pub use filter::{FilterQuery, SavedFilter};
+pub use info::{Feature, Info};
+pub use project::{project_paths, Project};
+pub use resolve::resolve;
+pub use select::TaskSelector;
+pub use share::LinkShare;
 pub use task::Comment;
 pub use task::Relation;
 pub use task::Task;
+pub use team::{Right, SharedTeam, SharedUser, Team};
+pub use token::TokenKind;
 
 use moka::sync::Cache;
+use once_cell::sync::OnceCell;
 use task::TaskRelation;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,9 +34,42 @@ pub struct VikunjaError {
     pub message: String,
 }
 
-impl VikunjaError {}
+impl VikunjaError {
+    /// Message of an error response.
+    ///
+    /// Vikunja errors carry a numeric `code` and a `message`, sometimes with
+    /// details like `invalid_fields`. A `message` alone is a success like
+    /// `{"message":"Successfully deleted."}`, errors of the web framework get
+    /// their HTTP status as `code` in `response_text`.
+    fn message(resp: &str) -> Option<String> {
+        let Ok(serde_json::Value::Object(obj)) = serde_json::from_str(resp) else {
+            return None;
+        };
+        let message = obj.get("message")?.as_str()?;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+        obj.get("code")
+            .is_some_and(serde_json::Value::is_i64)
+            .then(|| message.to_string())
+    }
+
+    /// Deserialize an API response or turn the returned error into a message
+    pub fn parse<T: serde::de::DeserializeOwned>(resp: &str) -> Result<T, String> {
+        // Models accept missing fields, so errors have to be caught before
+        if let Some(message) = Self::message(resp) {
+            return Err(message);
+        }
+
+        serde_json::from_str(resp).map_err(|e| e.to_string())
+    }
+
+    /// Turn an error returned instead of a plain message into an error
+    pub fn check(resp: &str) -> Result<(), String> {
+        Self::message(resp).map_or(Ok(()), Err)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Label {
     pub id: usize,
     pub title: String,
@@ -30,7 +80,8 @@ pub struct Label {
     pub created: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct User {
     pub id: usize,
     pub name: String,
@@ -59,19 +110,177 @@ where
 pub struct ProjectID(pub isize);
 
 impl ProjectID {
-    pub fn parse(api: &VikunjaAPI, project: &str) -> Option<Self> {
-        let project = project.trim_start_matches('#');
+    /// Resolve a project by ID (`42` or `#42`), identifier, title or path like `Work/Backend`
+    pub fn parse(api: &VikunjaAPI, project: &str) -> Result<Self, String> {
+        Self::parse_in(api, project, false)
+    }
+
+    /// Like `parse`, but archived projects can be given by name as well
+    pub fn parse_archived(api: &VikunjaAPI, project: &str) -> Result<Self, String> {
+        Self::parse_in(api, project, true)
+    }
+
+    fn parse_in(api: &VikunjaAPI, project: &str, archived: bool) -> Result<Self, String> {
+        api.require(Feature::Projects)?;
+
+        if let Ok(num) = project.trim_start_matches('#').parse() {
+            return Ok(Self(num));
+        }
+
+        let projects = api.get_projects(archived);
+        let paths = project_paths(&projects);
+
+        resolve(
+            "Project",
+            project,
+            &projects,
+            |x| {
+                [&x.title, &x.identifier, &paths[&x.id]]
+                    .into_iter()
+                    .filter(|name| !name.is_empty())
+                    .cloned()
+                    .collect()
+            },
+            |x| format!("{} [{}]", paths[&x.id], x.id),
+        )
+        .map(|x| Self(x.id))
+    }
+
+    /// Saved filters show up as pseudo projects with the ID `-filter_id - 1`
+    pub const fn from_saved_filter(filter_id: isize) -> Self {
+        Self(-filter_id - 1)
+    }
 
-        if let Ok(num) = project.parse() {
-            Some(Self(num))
+    /// The ID of the saved filter behind this pseudo project, if it is one.
+    ///
+    /// `-1` is the built-in favorites pseudo project and not a saved filter.
+    pub const fn saved_filter_id(&self) -> Option<isize> {
+        if self.0 < -1 {
+            Some(-self.0 - 1)
         } else {
-            Some(Self(
-                api.get_all_projects()
+            None
+        }
+    }
+}
+
+/// Reference to a task as given by the user
+#[derive(Debug, Clone)]
+pub enum TaskRef {
+    /// Global task ID, `123` or `#123`
+    Id(isize),
+    /// Per project index by project identifier, `BE-42`
+    Identifier(String, usize),
+    /// Per project index by project name, `Backend#42`
+    Project(String, usize),
+}
+
+impl std::str::FromStr for TaskRef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if let Ok(id) = s.trim_start_matches('#').parse() {
+            return Ok(Self::Id(id));
+        }
+
+        if let Some((project, index)) = s.rsplit_once('#') {
+            if let (false, Ok(index)) = (project.is_empty(), index.parse()) {
+                return Ok(Self::Project(project.to_string(), index));
+            }
+        }
+
+        if let Some((identifier, index)) = s.rsplit_once('-') {
+            if let (false, Ok(index)) = (identifier.is_empty(), index.parse()) {
+                return Ok(Self::Identifier(identifier.to_string(), index));
+            }
+        }
+
+        Err(format!(
+            "'{s}' is no task reference. Use an ID like `#123`, `BE-42` or `Backend#42`"
+        ))
+    }
+}
+
+impl TaskRef {
+    /// Resolve the reference to a global task ID
+    pub fn resolve(&self, api: &VikunjaAPI) -> Result<isize, String> {
+        let (project, index) = match self {
+            Self::Id(id) => return Ok(*id),
+            Self::Identifier(identifier, index) => {
+                let project = api
+                    .get_all_projects()
                     .into_iter()
-                    .find(|x| x.title.contains(project))?
-                    .id,
-            ))
+                    .find(|x| x.identifier.eq_ignore_ascii_case(identifier))
+                    .map_or_else(
+                        || Err(format!("No project with identifier '{identifier}'")),
+                        Ok,
+                    )?;
+                (ProjectID(project.id), *index)
+            }
+            Self::Project(project, index) => (ProjectID::parse(api, project)?, *index),
+        };
+
+        // Only fall back to all tasks for done tasks the project view may hide
+        let find = |tasks: Vec<Task>| {
+            tasks
+                .into_iter()
+                .find(|x| x.project_id == project.0 && x.index == index)
+        };
+        find(api.get_project_tasks(&project)?)
+            .or_else(|| find(api.get_all_tasks()))
+            .map(|x| x.id)
+            .map_or_else(|| Err(format!("Task '{self}' not found")), Ok)
+    }
+
+    /// The shortest unambiguous reference for `task` in `project`
+    pub fn display(task: &Task, project: Option<&Project>) -> String {
+        match project {
+            Some(project) if !project.identifier.is_empty() => {
+                format!("{}-{}", project.identifier, task.index)
+            }
+            Some(project) => format!("{}#{}", project.title, task.index),
+            None => format!("#{}", task.id),
+        }
+    }
+}
+
+impl std::fmt::Display for TaskRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Id(id) => write!(f, "#{id}"),
+            Self::Identifier(identifier, index) => write!(f, "{identifier}-{index}"),
+            Self::Project(project, index) => write!(f, "{project}#{index}"),
+        }
+    }
+}
+
+/// Labels to add and remove, given like `bug,+urgent,-later`
+#[derive(Debug, Default)]
+pub struct LabelChanges {
+    pub add: Vec<String>,
+    pub remove: Vec<String>,
+}
+
+impl LabelChanges {
+    /// Parse comma separated label lists. Labels without `+` or `-` are added.
+    pub fn parse<'a>(specs: impl IntoIterator<Item = &'a String>) -> Self {
+        let mut changes = Self::default();
+
+        for label in specs.into_iter().flat_map(|x| x.split(',')) {
+            let label = label.trim();
+            if let Some(label) = label.strip_prefix('-') {
+                changes.remove.push(label.trim().to_string());
+            } else if !label.is_empty() {
+                changes
+                    .add
+                    .push(label.trim_start_matches('+').trim().to_string());
+            }
         }
+
+        changes.add.retain(|x| !x.is_empty());
+        changes.remove.retain(|x| !x.is_empty());
+        changes
     }
 }
 
@@ -79,6 +288,8 @@ pub struct VikunjaAPI {
     host: String,
     token: String,
     cache: Cache<String, String>,
+    /// Server information, queried once when a feature is first checked
+    info: OnceCell<Option<Info>>,
 }
 
 impl VikunjaAPI {
@@ -87,79 +298,137 @@ impl VikunjaAPI {
             host: host.to_string(),
             token: token.to_string(),
             cache: Cache::new(100),
+            info: OnceCell::new(),
+        }
+    }
+
+    /// Server version and features, `None` if the server did not tell
+    pub fn info(&self) -> Option<&Info> {
+        self.info
+            .get_or_init(|| {
+                let resp = reqwest::blocking::Client::new()
+                    .get(format!("{}/api/v1/info", self.host))
+                    .send()
+                    .ok()?
+                    .text()
+                    .ok()?;
+                VikunjaError::parse(&resp).ok()
+            })
+            .as_ref()
+    }
+
+    /// Fail with a clear message if the server does not support `feature`
+    pub fn require(&self, feature: Feature) -> Result<(), String> {
+        match self.info() {
+            Some(info) if !info.supports(feature) => Err(format!(
+                "Your server (Vikunja {}) does not support {}",
+                info.version,
+                feature.repr()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Body of a response.
+    ///
+    /// Failed requests without a Vikunja error code get the HTTP status as
+    /// `code`, so `VikunjaError` recognizes them as errors.
+    pub fn response_text(resp: reqwest::blocking::Response) -> String {
+        let status = resp.status();
+        let text = resp.text().unwrap();
+
+        if !(status.is_client_error() || status.is_server_error())
+            || VikunjaError::message(&text).is_some()
+        {
+            return text;
         }
+
+        let message = serde_json::from_str::<serde_json::Value>(&text)
+            .ok()
+            .and_then(|x| Some(x.get("message")?.as_str()?.to_string()))
+            .unwrap_or_else(|| status.to_string());
+        serde_json::json!({ "code": status.as_u16(), "message": message }).to_string()
     }
 
     fn get_request(&self, path: &str) -> String {
-        self.cache.get(path).map_or_else(
-            || {
-                let client = reqwest::blocking::Client::new();
+        self.cache.get(path).unwrap_or_else(|| {
+            let client = reqwest::blocking::Client::new();
 
-                let ret = client
+            let ret = Self::response_text(
+                client
                     .get(format!("{}/api/v1{}", self.host, path))
                     .header("Authorization", format!("Bearer {}", self.token))
                     .send()
-                    .unwrap()
-                    .text()
-                    .unwrap();
+                    .unwrap(),
+            );
 
-                self.cache.insert(path.to_string(), ret.clone());
-                ret
-            },
-            |cached| cached,
-        )
+            self.cache.insert(path.to_string(), ret.clone());
+            ret
+        })
     }
 
     fn put_request(&self, path: &str, data: &serde_json::Value) -> String {
         let client = reqwest::blocking::Client::new();
 
-        client
-            .put(format!("{}/api/v1{}", self.host, path))
-            .header("Authorization", format!("Bearer {}", self.token))
-            .json(&data)
-            .send()
-            .unwrap()
-            .text()
-            .unwrap()
+        Self::response_text(
+            client
+                .put(format!("{}/api/v1{}", self.host, path))
+                .header("Authorization", format!("Bearer {}", self.token))
+                .json(&data)
+                .send()
+                .unwrap(),
+        )
     }
 
     fn post_request(&self, path: &str, data: &serde_json::Value) -> String {
         let client = reqwest::blocking::Client::new();
 
-        client
-            .post(format!("{}/api/v1{}", self.host, path))
-            .header("Authorization", format!("Bearer {}", self.token))
-            .json(&data)
-            .send()
-            .unwrap()
-            .text()
-            .unwrap()
+        Self::response_text(
+            client
+                .post(format!("{}/api/v1{}", self.host, path))
+                .header("Authorization", format!("Bearer {}", self.token))
+                .json(&data)
+                .send()
+                .unwrap(),
+        )
     }
 
     fn delete_request(&self, path: &str) -> String {
         let client = reqwest::blocking::Client::new();
 
-        client
-            .delete(format!("{}/api/v1{}", self.host, path))
-            .header("Authorization", format!("Bearer {}", self.token))
-            .send()
-            .unwrap()
-            .text()
-            .unwrap()
+        Self::response_text(
+            client
+                .delete(format!("{}/api/v1{}", self.host, path))
+                .header("Authorization", format!("Bearer {}", self.token))
+                .send()
+                .unwrap(),
+        )
     }
 
-    // projects
-
-    pub fn get_project_name_from_id(&self, id: isize) -> String {
-        let all_prj = self.get_all_projects();
+    /// Forget all cached responses, for long running sessions like the TUI
+    pub fn clear_cache(&self) {
+        self.cache.invalidate_all();
+    }
 
-        let found = all_prj.into_iter().find(|x| x.id == id).unwrap();
+    // projects
 
-        found.title
+    pub fn get_project_name_from_id(&self, id: isize) -> Result<String, String> {
+        let resp = self.get_request(&format!("/projects/{id}"));
+        VikunjaError::parse::<Project>(&resp).map(|x| x.title)
     }
 
+    /// All projects which are not archived
     pub fn get_all_projects(&self) -> Vec<Project> {
-        let resp = self.get_request("/projects");
+        self.get_projects(false)
+    }
+
+    /// All projects, including archived ones if `archived`
+    pub fn get_projects(&self, archived: bool) -> Vec<Project> {
+        let resp = self.get_request(if archived {
+            "/projects?is_archived=true"
+        } else {
+            "/projects"
+        });
         serde_json::from_str(&resp).unwrap()
     }
 
@@ -190,6 +459,95 @@ impl VikunjaAPI {
         serde_json::from_str(&resp).unwrap()
     }
 
+    /// Update a project. Vikunja expects the full project on update.
+    pub fn update_project(&self, project: &Project) -> Result<Project, String> {
+        let resp = self.post_request(
+            &format!("/projects/{}", project.id),
+            &serde_json::to_value(project).unwrap(),
+        );
+        VikunjaError::parse(&resp)
+    }
+
+    pub fn archive_project(&self, project: &ProjectID, archived: bool) -> Result<Project, String> {
+        let mut project = self.get_project(project);
+        project.is_archived = archived;
+        self.update_project(&project)
+    }
+
+    pub fn duplicate_project(
+        &self,
+        project: &ProjectID,
+        parent: Option<ProjectID>,
+    ) -> Result<Project, String> {
+        let resp = self.put_request(
+            &format!("/projects/{}/duplicate", project.0),
+            &serde_json::json!({
+                "parent_project_id": parent.map_or(0, |x| x.0)
+            }),
+        );
+        let val: serde_json::Value = VikunjaError::parse(&resp)?;
+        serde_json::from_value(val["duplicated_project"].clone()).map_err(|e| e.to_string())
+    }
+
+    pub fn get_project_tasks(&self, project: &ProjectID) -> Result<Vec<Task>, String> {
+        let mut error = None;
+        let tasks = get_all_items(|x| {
+            let resp = self.get_request(&format!("/projects/{}/tasks?page={x}", project.0));
+            if resp.trim() == "null" {
+                return Vec::new();
+            }
+            VikunjaError::parse(&resp).unwrap_or_else(|msg| {
+                error = Some(msg);
+                Vec::new()
+            })
+        });
+        error.map_or(Ok(tasks), Err)
+    }
+
+    // saved filters
+
+    pub fn get_saved_filters(&self) -> Vec<SavedFilter> {
+        self.get_all_projects()
+            .into_iter()
+            .filter_map(|x| ProjectID(x.id).saved_filter_id())
+            .filter_map(|x| self.get_saved_filter(x).ok())
+            .collect()
+    }
+
+    pub fn get_saved_filter(&self, filter_id: isize) -> Result<SavedFilter, String> {
+        let resp = self.get_request(&format!("/filters/{filter_id}"));
+        VikunjaError::parse(&resp)
+    }
+
+    pub fn new_saved_filter(
+        &self,
+        title: &str,
+        description: Option<&str>,
+        query: &FilterQuery,
+    ) -> Result<SavedFilter, String> {
+        let resp = self.put_request(
+            "/filters",
+            &serde_json::json!({
+                "title": title,
+                "description": description.unwrap_or_default(),
+                "filters": query
+            }),
+        );
+        VikunjaError::parse(&resp)
+    }
+
+    pub fn update_saved_filter(&self, filter: &SavedFilter) -> Result<SavedFilter, String> {
+        let resp = self.post_request(
+            &format!("/filters/{}", filter.id),
+            &serde_json::to_value(filter).unwrap(),
+        );
+        VikunjaError::parse(&resp)
+    }
+
+    pub fn delete_saved_filter(&self, filter_id: isize) {
+        self.delete_request(&format!("/filters/{filter_id}"));
+    }
+
     // labels
     pub fn get_all_labels(&self) -> Vec<Label> {
         get_all_items(|x| {
@@ -213,47 +571,112 @@ impl VikunjaAPI {
         serde_json::from_str(&resp).unwrap()
     }
 
-    pub fn remove_label(&self, title: &str) {
-        let labels = self.get_all_labels();
+    pub fn update_label(&self, label: &Label) -> Result<Label, String> {
+        let resp = self.post_request(
+            &format!("/labels/{}", label.id),
+            &serde_json::to_value(label).unwrap(),
+        );
+        VikunjaError::parse(&resp)
+    }
+
+    /// Move `from` on every task to `into` and delete `from` afterwards.
+    ///
+    /// Returns the amount of relabeled tasks.
+    pub fn merge_labels(&self, from: &Label, into: &Label) -> Result<usize, String> {
+        if from.id == into.id {
+            return Err(format!("Can not merge '{}' into itself", from.title));
+        }
 
-        let label_id = labels
+        let tasks: Vec<Task> = self
+            .get_all_tasks()
             .into_iter()
-            .find(|x| x.title.trim() == title)
-            .unwrap()
-            .id;
+            .filter(|x| x.labels.iter().flatten().any(|l| l.id == from.id))
+            .collect();
+
+        for task in &tasks {
+            if !task.labels.iter().flatten().any(|l| l.id == into.id) {
+                let resp = self.put_request(
+                    &format!("/tasks/{}/labels", task.id),
+                    &serde_json::json!({ "label_id": into.id }),
+                );
+                VikunjaError::check(&resp)?;
+            }
+            let resp = self.delete_request(&format!("/tasks/{}/labels/{}", task.id, from.id));
+            VikunjaError::check(&resp)?;
+        }
 
-        self.delete_request(&format!("/labels/{label_id}"));
+        let resp = self.delete_request(&format!("/labels/{}", from.id));
+        VikunjaError::check(&resp)?;
+        Ok(tasks.len())
     }
 
-    pub fn label_task_remove(&self, label: &str, task_id: isize) {
-        let labels = self.get_all_labels();
+    /// Resolve a label by ID or title
+    pub fn resolve_label(&self, label: &str) -> Result<Label, String> {
+        Self::resolve_label_in(label, &self.get_all_labels())
+    }
 
-        let label_id = labels
-            .into_iter()
-            .find(|x| x.title.trim() == label)
-            .unwrap()
-            .id;
+    /// Resolve a label by ID or exact title, ignoring case, among `labels`
+    pub fn resolve_label_in(label: &str, labels: &[Label]) -> Result<Label, String> {
+        if let Ok(id) = label.trim_start_matches('#').parse::<usize>() {
+            if let Some(found) = labels.iter().find(|x| x.id == id) {
+                return Ok(found.clone());
+            }
+        }
 
-        self.delete_request(&format!("/tasks/{task_id}/labels/{label_id}"));
+        resolve(
+            "Label",
+            label,
+            labels,
+            |x| vec![x.title.clone()],
+            |x| format!("{} [{}]", x.title.trim(), x.id),
+        )
+        .cloned()
     }
 
-    pub fn label_task(&self, label: &str, task_id: isize) -> Result<(), String> {
-        let labels = self.get_all_labels();
+    pub fn remove_label(&self, title: &str) -> Result<(), String> {
+        let label_id = self.resolve_label(title)?.id;
 
-        let label_id = labels
-            .into_iter()
-            .find(|x| x.title.trim() == label)
-            .map_or_else(|| Err(format!("Label '{label}' not found")), Ok)?
-            .id;
+        self.delete_request(&format!("/labels/{label_id}"));
+        Ok(())
+    }
 
-        self.put_request(
-            &format!("/tasks/{task_id}/labels"),
-            &serde_json::json!({
-                "label_id": label_id
-            }),
+    /// Replace all labels of a task with `labels`
+    pub fn set_task_labels(&self, task_id: isize, labels: &[Label]) -> Result<(), String> {
+        let resp = self.post_request(
+            &format!("/tasks/{task_id}/labels/bulk"),
+            &serde_json::json!({ "labels": labels }),
         );
+        self.cache.invalidate(&format!("/tasks/{task_id}"));
 
-        Ok(())
+        VikunjaError::check(&resp)
+    }
+
+    /// Add and remove labels of a task, only touching the server on a change
+    pub fn change_task_labels(
+        &self,
+        task: &Task,
+        add: &[Label],
+        remove: &[Label],
+    ) -> Result<(), String> {
+        let current = task.labels.clone().unwrap_or_default();
+
+        let mut labels: Vec<Label> = current
+            .iter()
+            .filter(|x| !remove.iter().any(|r| r.id == x.id))
+            .cloned()
+            .collect();
+        for label in add {
+            if !labels.iter().any(|x| x.id == label.id) {
+                labels.push(label.clone());
+            }
+        }
+
+        let ids = |labels: &[Label]| labels.iter().map(|x| x.id).collect::<Vec<_>>();
+        if ids(&labels) == ids(&current) {
+            return Ok(());
+        }
+
+        self.set_task_labels(task.id, &labels)
     }
 
     // tasks
@@ -273,13 +696,24 @@ impl VikunjaAPI {
 
     pub fn get_task(&self, id: isize) -> Result<Task, ()> {
         let resp = self.get_request(&format!("/tasks/{id}"));
-        serde_json::from_str(&resp).map_or(Err(()), Ok)
+        VikunjaError::parse(&resp).map_err(|_| ())
+    }
+
+    /// Update a task. Vikunja expects the full task on update.
+    pub fn update_task(&self, task: &Task) -> Result<Task, String> {
+        let resp = self.post_request(
+            &format!("/tasks/{}", task.id),
+            &serde_json::to_value(task).unwrap(),
+        );
+        self.cache.invalidate(&format!("/tasks/{}", task.id));
+        VikunjaError::parse(&resp)
     }
 
     pub fn delete_task(&self, id: isize) {
         self.delete_request(&format!("/tasks/{id}"));
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new_task(
         &self,
         title: &str,
@@ -287,36 +721,161 @@ impl VikunjaAPI {
         description: Option<String>,
         due_date: Option<String>,
         fav: bool,
-        label: Option<String>,
+        labels: &[Label],
         priority: Option<isize>,
     ) -> Result<Task, String> {
         let id = project.0;
 
-        let labels = if let Some(label) = label {
-            let label = self
-                .get_all_labels()
-                .into_iter()
-                .find(|x| x.title.trim() == label)
-                .map_or_else(|| Err(format!("Label '{label}' not found")), Ok)?;
-            vec![label]
-        } else {
-            vec![]
-        };
-
         let data = serde_json::json!({
             "title": title,
             "description": description,
             "due_date": due_date,
             "is_favorite": fav,
-            "priority": priority,
-            "labels": labels
+            "priority": priority
         });
 
         let resp = self.put_request(&format!("/projects/{id}/tasks"), &data);
-        Ok(serde_json::from_str(&resp).unwrap())
+        let task: Task = VikunjaError::parse(&resp)?;
+
+        if !labels.is_empty() {
+            self.set_task_labels(task.id, labels)?;
+        }
+
+        Ok(task)
     }
 
-    pub fn done_task(&self, task_id: isize, done: bool) -> Option<Task> {
+    /// Move a task to another project and optionally into a bucket
+    pub fn move_task(
+        &self,
+        task_id: isize,
+        project: &ProjectID,
+        bucket: Option<usize>,
+    ) -> Result<Task, String> {
+        if project.0 < 0 {
+            return Err(String::from("Tasks can not be moved into a saved filter"));
+        }
+
+        if bucket.is_some() {
+            self.require(Feature::TaskBuckets)?;
+        }
+
+        let mut task = self
+            .get_task(task_id)
+            .map_err(|()| format!("Could not get task #{task_id}"))?;
+        task.project_id = project.0;
+        // The bucket `0` lets Vikunja pick the default bucket of the project
+        task.bucket_id = bucket.unwrap_or(0);
+        self.update_task(&task)
+    }
+
+    /// Create a new task in `project_id` from the fields, labels, assignees and
+    /// relations of `task`. Relations to tasks which no longer exist are skipped.
+    pub fn recreate_task(&self, task: &Task, project_id: isize) -> Result<Task, String> {
+        let resp = self.put_request(
+            &format!("/projects/{project_id}/tasks"),
+            &serde_json::json!({
+                "title": task.title,
+                "description": task.description,
+                "done": task.done,
+                "due_date": task.due_date,
+                "start_date": task.start_date,
+                "end_date": task.end_date,
+                "priority": task.priority,
+                "hex_color": task.hex_color,
+                "percent_done": task.percent_done,
+                "repeat_after": task.repeat_after,
+                "repeat_mode": task.repeat_mode,
+                "is_favorite": task.is_favorite,
+                "reminders": task.reminders
+            }),
+        );
+        let copy: Task = VikunjaError::parse(&resp)?;
+
+        for label in task.labels.iter().flatten() {
+            let resp = self.put_request(
+                &format!("/tasks/{}/labels", copy.id),
+                &serde_json::json!({ "label_id": label.id }),
+            );
+            VikunjaError::check(&resp)
+                .map_err(|msg| format!("Could not add label '{}': {msg}", label.title))?;
+        }
+
+        for user in task.assignees.iter().flatten() {
+            let resp = self.put_request(
+                &format!("/tasks/{}/assignees", copy.id),
+                &serde_json::json!({ "user_id": user.id }),
+            );
+            VikunjaError::check(&resp)
+                .map_err(|msg| format!("Could not assign '{}': {msg}", user.username))?;
+        }
+
+        for (kind, related) in task.related_tasks.iter().flatten() {
+            let Some(relation) = Relation::try_parse(kind) else {
+                continue;
+            };
+            if matches!(relation, Relation::CopiedFrom | Relation::CopiedTo) {
+                continue;
+            }
+            for other in related {
+                if self.get_task(other.id).is_ok() {
+                    self.add_relation(copy.id, &relation, other.id)?;
+                }
+            }
+        }
+
+        Ok(copy)
+    }
+
+    /// Copy a task with its labels, assignees, reminders and relations.
+    ///
+    /// The copy is placed in `project` or the project of the original task and
+    /// gets a `copiedfrom` relation to the original.
+    pub fn duplicate_task(
+        &self,
+        task_id: isize,
+        project: Option<&ProjectID>,
+    ) -> Result<Task, String> {
+        let task = self
+            .get_task(task_id)
+            .map_err(|()| format!("Could not get task #{task_id}"))?;
+
+        let copy = self.recreate_task(&task, project.map_or(task.project_id, |x| x.0))?;
+        self.add_relation(copy.id, &Relation::CopiedFrom, task.id)?;
+
+        Ok(copy)
+    }
+
+    /// Set the progress of a task between `0.0` and `1.0`
+    pub fn set_task_progress(&self, task_id: isize, percent_done: f64) -> Result<Task, String> {
+        let mut task = self
+            .get_task(task_id)
+            .map_err(|()| format!("Could not get task #{task_id}"))?;
+        task.percent_done = percent_done;
+        self.update_task(&task)
+    }
+
+    pub fn set_task_description(&self, task_id: isize, description: &str) -> Result<Task, String> {
+        let mut task = self
+            .get_task(task_id)
+            .map_err(|()| format!("Could not get task #{task_id}"))?;
+        task.description = description.to_string();
+        self.update_task(&task)
+    }
+
+    /// Update the same fields on several tasks at once
+    pub fn bulk_update_tasks(
+        &self,
+        task_ids: &[isize],
+        data: &serde_json::Value,
+    ) -> Result<Vec<Task>, String> {
+        let mut data = data.clone();
+        data["task_ids"] = serde_json::json!(task_ids);
+
+        let resp = self.post_request("/tasks/bulk", &data);
+        VikunjaError::parse(&resp)
+    }
+
+    pub fn done_task(&self, task_id: isize, done: bool) -> Result<Task, String> {
         let resp = self.post_request(
             &format!("/tasks/{task_id}"),
             &serde_json::json!({
@@ -324,18 +883,20 @@ impl VikunjaAPI {
                 "done_at": if done { Some(chrono::Utc::now().to_rfc3339()) } else { None }
             }),
         );
-        serde_json::from_str(&resp).ok()
+        self.cache.invalidate(&format!("/tasks/{task_id}"));
+        VikunjaError::parse(&resp)
     }
 
-    pub fn fav_task(&self, task_id: isize, fav: bool) -> Option<Task> {
+    pub fn fav_task(&self, task_id: isize, fav: bool) -> Result<Task, String> {
         let resp = self.post_request(
             &format!("/tasks/{task_id}"),
             &serde_json::json!({
                 "is_favorite": fav
             }),
         );
+        self.cache.invalidate(&format!("/tasks/{task_id}"));
 
-        serde_json::from_str(&resp).ok()
+        VikunjaError::parse(&resp)
     }
 
     pub fn login(&self, username: &str, password: &str, totp: Option<&str>) -> String {
@@ -364,25 +925,134 @@ impl VikunjaAPI {
         serde_json::from_str(&resp).ok()
     }
 
-    pub fn assign_to_task(&self, user: &str, task_id: isize) -> Result<(), String> {
-        let user = self
-            .search_user(user)
-            .map_or_else(|| Err(String::from("User not found")), Ok)?;
+    /// The logged in user
+    pub fn current_user(&self) -> Result<User, String> {
+        let resp = self.get_request("/user");
+        VikunjaError::parse(&resp)
+    }
+
+    /// General settings of the logged in user.
+    ///
+    /// Kept as JSON, because the server replaces all of them on updates.
+    pub fn get_user_settings(&self) -> Result<serde_json::Value, String> {
+        let resp = self.get_request("/user");
+        let user: serde_json::Value = VikunjaError::parse(&resp)?;
+
+        user.get("settings").cloned().map_or_else(
+            || Err(String::from("The server returned no user settings")),
+            Ok,
+        )
+    }
+
+    pub fn update_user_settings(&self, settings: &serde_json::Value) -> Result<(), String> {
+        let resp = self.post_request("/user/settings/general", settings);
+        self.cache.invalidate("/user");
+        VikunjaError::check(&resp)
+    }
+
+    /// Project for new tasks, the default project of the logged in user or the Inbox
+    pub fn default_project(&self) -> Option<ProjectID> {
+        self.get_user_settings()
+            .ok()
+            .and_then(|x| x.get("default_project_id")?.as_i64())
+            .filter(|x| *x > 0)
+            .map(|x| ProjectID(x as isize))
+            .or_else(|| ProjectID::parse(self, "Inbox").ok())
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Resolve a user among `users` by username, display names are not unique
+    fn resolve_user_in(&self, user: &str, users: &[User]) -> Result<User, String> {
+        if user == "me" {
+            return self.current_user();
+        }
+
+        resolve(
+            "User",
+            user,
+            users,
+            |x| vec![x.username.clone()],
+            |x| format!("{} ({})", x.username, x.name),
+        )
+        .cloned()
+    }
+
+    /// Resolve a user by username, `me` is the logged in user
+    pub fn resolve_user(&self, user: &str) -> Result<User, String> {
+        self.resolve_user_in(user, &self.search_user(user).unwrap_or_default())
+    }
+
+    /// Owner and everyone a project or one of its parents is shared with
+    pub fn project_members(&self, project_id: isize) -> Vec<User> {
+        let projects = self.get_all_projects();
+        let mut members: Vec<User> = Vec::new();
+        let mut add = |user: User| {
+            if !members.iter().any(|x| x.id == user.id) {
+                members.push(user);
+            }
+        };
+
+        let mut current = projects.iter().find(|x| x.id == project_id);
+        let mut seen = Vec::new();
+
+        while let Some(prj) = current.filter(|x| !seen.contains(&x.id)) {
+            seen.push(prj.id);
+            let id = ProjectID(prj.id);
+
+            if let Some(owner) = &prj.owner {
+                add(owner.clone());
+            }
+            for shared in self.get_project_users(&id) {
+                add(shared.user);
+            }
+            for shared in self.get_project_teams(&id) {
+                for member in shared.team.members.unwrap_or_default() {
+                    add(member.user);
+                }
+            }
+
+            current = projects.iter().find(|x| x.id == prj.parent_project_id);
+        }
 
-        self.put_request(
+        members
+    }
+
+    /// Resolve a user who can be assigned to tasks of the project `project_id`
+    pub fn resolve_assignee(&self, user: &str, project_id: isize) -> Result<User, String> {
+        let members = self.project_members(project_id);
+
+        self.resolve_user_in(user, &members)
+            .map_err(|msg| match self.resolve_user(user) {
+                Ok(found) if !members.iter().any(|x| x.id == found.id) => format!(
+                    "User '{}' has no access to project '{}'",
+                    found.username,
+                    self.get_project_name_from_id(project_id)
+                        .unwrap_or_else(|_| format!("#{project_id}"))
+                ),
+                _ => msg,
+            })
+    }
+
+    pub fn assign_to_task(&self, user: &User, task_id: isize) -> Result<(), String> {
+        let resp = self.put_request(
             &format!("/tasks/{task_id}/assignees"),
             &serde_json::json!({
-                "user_id": user.first().unwrap().id
+                "user_id": user.id
             }),
         );
+        self.cache.invalidate(&format!("/tasks/{task_id}"));
 
-        Ok(())
+        VikunjaError::check(&resp)
     }
 
-    pub fn remove_assign_to_task(&self, user: &str, task_id: isize) {
-        let user = self.search_user(user).unwrap();
-        let user_id = user.first().unwrap().id;
-        self.delete_request(&format!("/tasks/{task_id}/assignees/{user_id}"));
+    pub fn remove_assign_to_task(&self, user: &User, task_id: isize) -> Result<(), String> {
+        let resp = self.delete_request(&format!("/tasks/{task_id}/assignees/{}", user.id));
+        self.cache.invalidate(&format!("/tasks/{task_id}"));
+
+        VikunjaError::check(&resp)
     }
 
     pub fn get_task_comments(&self, task_id: isize) -> Vec<Comment> {
@@ -395,6 +1065,8 @@ impl VikunjaAPI {
             "/tasks/{task_id}/relations/{}/{other_task_id}",
             relation.api()
         ));
+        self.cache.invalidate(&format!("/tasks/{task_id}"));
+        self.cache.invalidate(&format!("/tasks/{other_task_id}"));
     }
 
     pub fn add_relation(
@@ -402,7 +1074,7 @@ impl VikunjaAPI {
         task_id: isize,
         relation: &Relation,
         other_task_id: isize,
-    ) -> TaskRelation {
+    ) -> Result<TaskRelation, String> {
         let resp = self.put_request(
             &format!("/tasks/{task_id}/relations"),
             &serde_json::json!({
@@ -411,16 +1083,335 @@ impl VikunjaAPI {
                 "relation_kind": relation.api()
             }),
         );
-        serde_json::from_str(&resp).unwrap()
+        self.cache.invalidate(&format!("/tasks/{task_id}"));
+        self.cache.invalidate(&format!("/tasks/{other_task_id}"));
+        VikunjaError::parse(&resp)
+    }
+
+    /// Add a relation and the inverse relation on the other task if the server did not
+    pub fn relate_tasks(
+        &self,
+        task_id: isize,
+        relation: &Relation,
+        other_task_id: isize,
+    ) -> Result<(), String> {
+        self.add_relation(task_id, relation, other_task_id)?;
+
+        let Some(inverse) = relation.inverse() else {
+            return Ok(());
+        };
+
+        let other = self
+            .get_task(other_task_id)
+            .map_err(|()| format!("Could not get task #{other_task_id}"))?;
+
+        if !other
+            .related(&inverse.api())
+            .iter()
+            .any(|x| x.id == task_id)
+        {
+            self.add_relation(other_task_id, &inverse, task_id)?;
+        }
+
+        Ok(())
     }
 
-    pub fn new_comment(&self, task_id: isize, comment: &str) -> Comment {
+    pub fn new_comment(&self, task_id: isize, comment: &str) -> Result<Comment, String> {
         let resp = self.put_request(
             &format!("/tasks/{task_id}/comments"),
             &serde_json::json!({
                 "comment": comment
             }),
         );
-        serde_json::from_str(&resp).unwrap()
+        VikunjaError::parse(&resp)
+    }
+
+    pub fn get_task_comment(&self, task_id: isize, comment_id: isize) -> Result<Comment, String> {
+        let resp = self.get_request(&format!("/tasks/{task_id}/comments/{comment_id}"));
+        VikunjaError::parse(&resp)
+    }
+
+    pub fn update_comment(
+        &self,
+        task_id: isize,
+        comment_id: isize,
+        comment: &str,
+    ) -> Result<Comment, String> {
+        let resp = self.post_request(
+            &format!("/tasks/{task_id}/comments/{comment_id}"),
+            &serde_json::json!({
+                "comment": comment
+            }),
+        );
+        VikunjaError::parse(&resp)
+    }
+
+    pub fn delete_comment(&self, task_id: isize, comment_id: isize) -> Result<(), String> {
+        let resp = self.delete_request(&format!("/tasks/{task_id}/comments/{comment_id}"));
+        VikunjaError::check(&resp)
+    }
+
+    // teams
+    pub fn get_teams(&self) -> Vec<Team> {
+        get_all_items(|x| {
+            let resp = self.get_request(&format!("/teams?page={x}"));
+            if resp.trim() == "null" {
+                return Vec::new();
+            }
+            serde_json::from_str(&resp).unwrap()
+        })
+    }
+
+    /// Resolve a team by ID or name
+    pub fn resolve_team(&self, team: &str) -> Result<Team, String> {
+        let teams = self.get_teams();
+
+        if let Ok(id) = team.trim_start_matches('#').parse::<isize>() {
+            if let Some(found) = teams.iter().find(|x| x.id == id) {
+                return Ok(found.clone());
+            }
+        }
+
+        resolve(
+            "Team",
+            team,
+            &teams,
+            |x| vec![x.name.clone()],
+            |x| format!("{} [{}]", x.name, x.id),
+        )
+        .cloned()
+    }
+
+    pub fn new_team(&self, name: &str, description: Option<&str>) -> Result<Team, String> {
+        let resp = self.put_request(
+            "/teams",
+            &serde_json::json!({
+                "name": name,
+                "description": description.unwrap_or_default()
+            }),
+        );
+        VikunjaError::parse(&resp)
+    }
+
+    pub fn add_team_member(&self, team: &Team, user: &User, admin: bool) -> Result<(), String> {
+        let resp = self.put_request(
+            &format!("/teams/{}/members", team.id),
+            &serde_json::json!({
+                "username": user.username,
+                "admin": admin
+            }),
+        );
+        VikunjaError::check(&resp)
+    }
+
+    pub fn remove_team_member(&self, team: &Team, user: &User) -> Result<(), String> {
+        let resp = self.delete_request(&format!("/teams/{}/members/{}", team.id, user.username));
+        VikunjaError::check(&resp)
+    }
+
+    // sharing
+    pub fn get_link_shares(&self, project: &ProjectID) -> Vec<LinkShare> {
+        let resp = self.get_request(&format!("/projects/{}/shares", project.0));
+        serde_json::from_str(&resp).unwrap_or_default()
+    }
+
+    pub fn new_link_share(
+        &self,
+        project: &ProjectID,
+        right: Right,
+        name: Option<&str>,
+        password: Option<&str>,
+    ) -> Result<LinkShare, String> {
+        let resp = self.put_request(
+            &format!("/projects/{}/shares", project.0),
+            &serde_json::json!({
+                "right": right.api(),
+                "name": name.unwrap_or_default(),
+                "password": password.unwrap_or_default(),
+                "sharing_type": if password.is_some() { 2 } else { 1 }
+            }),
+        );
+        VikunjaError::parse(&resp)
+    }
+
+    /// Resolve a link share of a project by ID or hash
+    pub fn resolve_link_share(
+        &self,
+        project: &ProjectID,
+        share: &str,
+    ) -> Result<LinkShare, String> {
+        let shares = self.get_link_shares(project);
+
+        shares
+            .iter()
+            .find(|x| x.hash == share || share.parse() == Ok(x.id))
+            .cloned()
+            .map_or_else(|| Err(format!("Link share '{share}' not found")), Ok)
+    }
+
+    pub fn delete_link_share(&self, project: &ProjectID, share: &LinkShare) -> Result<(), String> {
+        let resp = self.delete_request(&format!("/projects/{}/shares/{}", project.0, share.id));
+        VikunjaError::check(&resp)
+    }
+
+    /// Public URL of a link share in the web interface
+    pub fn link_share_url(&self, share: &LinkShare) -> String {
+        format!(
+            "{}/share/{}/auth",
+            self.host.trim_end_matches('/'),
+            share.hash
+        )
+    }
+
+    /// Get a token for accessing the project of the link share `hash`
+    pub fn link_share_auth(&self, hash: &str, password: Option<&str>) -> Result<String, String> {
+        self.require(Feature::LinkShares)?;
+
+        let resp = self.post_request(
+            &format!("/shares/{hash}/auth"),
+            &serde_json::json!({
+                "password": password.unwrap_or_default()
+            }),
+        );
+
+        #[derive(Deserialize)]
+        struct Token {
+            token: String,
+        }
+
+        VikunjaError::parse::<Token>(&resp).map(|x| x.token)
+    }
+
+    pub fn get_project_users(&self, project: &ProjectID) -> Vec<SharedUser> {
+        let resp = self.get_request(&format!("/projects/{}/users", project.0));
+        serde_json::from_str(&resp).unwrap_or_default()
+    }
+
+    pub fn get_project_teams(&self, project: &ProjectID) -> Vec<SharedTeam> {
+        let resp = self.get_request(&format!("/projects/{}/teams", project.0));
+        serde_json::from_str(&resp).unwrap_or_default()
+    }
+
+    /// Share a project with a user or change the right of a user it is shared with
+    pub fn share_project_with_user(
+        &self,
+        project: &ProjectID,
+        user: &User,
+        right: Right,
+    ) -> Result<(), String> {
+        let shared = self
+            .get_project_users(project)
+            .iter()
+            .any(|x| x.user.id == user.id);
+
+        let resp = if shared {
+            self.post_request(
+                &format!("/projects/{}/users/{}", project.0, user.id),
+                &serde_json::json!({ "right": right.api() }),
+            )
+        } else {
+            self.put_request(
+                &format!("/projects/{}/users", project.0),
+                &serde_json::json!({
+                    "username": user.username,
+                    "right": right.api()
+                }),
+            )
+        };
+        VikunjaError::check(&resp)
+    }
+
+    /// Share a project with a team or change the right of a team it is shared with
+    pub fn share_project_with_team(
+        &self,
+        project: &ProjectID,
+        team: &Team,
+        right: Right,
+    ) -> Result<(), String> {
+        let shared = self
+            .get_project_teams(project)
+            .iter()
+            .any(|x| x.team.id == team.id);
+
+        let resp = if shared {
+            self.post_request(
+                &format!("/projects/{}/teams/{}", project.0, team.id),
+                &serde_json::json!({ "right": right.api() }),
+            )
+        } else {
+            self.put_request(
+                &format!("/projects/{}/teams", project.0),
+                &serde_json::json!({
+                    "team_id": team.id,
+                    "right": right.api()
+                }),
+            )
+        };
+        VikunjaError::check(&resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_without_code_is_success() {
+        assert_eq!(
+            VikunjaError::check(r#"{"message":"Successfully deleted."}"#),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn message_with_code_is_error() {
+        assert_eq!(
+            VikunjaError::check(r#"{"code":3001,"message":"The project does not exist."}"#),
+            Err("The project does not exist.".to_string())
+        );
+        assert_eq!(
+            VikunjaError::check(
+                r#"{"code":2002,"message":"Invalid data","invalid_fields":["title"]}"#
+            ),
+            Err("Invalid data".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_reports_error_message() {
+        let resp = r#"{"code":404,"message":"Not Found"}"#;
+        assert_eq!(
+            VikunjaError::parse::<Vec<Label>>(resp).unwrap_err(),
+            "Not Found"
+        );
+    }
+
+    fn task_ref(s: &str) -> TaskRef {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_task_refs() {
+        assert!(matches!(task_ref("12"), TaskRef::Id(12)));
+        assert!(matches!(task_ref("#12"), TaskRef::Id(12)));
+        assert!(matches!(task_ref("BE-42"), TaskRef::Identifier(x, 42) if x == "BE"));
+        assert!(matches!(task_ref("Backend#42"), TaskRef::Project(x, 42) if x == "Backend"));
+        assert!(matches!(task_ref("Front-End#7"), TaskRef::Project(x, 7) if x == "Front-End"));
+    }
+
+    #[test]
+    fn reject_task_refs() {
+        for s in ["", "Backend", "BE-", "#", "Backend#x"] {
+            assert!(s.parse::<TaskRef>().is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn parse_label_changes() {
+        let specs = ["+a,-b".to_string(), " c , - d ,+,".to_string()];
+        let changes = LabelChanges::parse(&specs);
+
+        assert_eq!(changes.add, ["a", "c"]);
+        assert_eq!(changes.remove, ["b", "d"]);
     }
 }
@@ -0,0 +1,71 @@
+use std::collections::{HashMap, HashSet};
+
+use super::Task;
+
+/// Progress of a task between `0.0` and `1.0`.
+///
+/// Done tasks are complete. Tasks with subtasks get the average progress of
+/// their subtasks, all other tasks their own `percent_done`.
+pub fn task_progress(task: &Task, tasks: &HashMap<isize, Task>) -> f64 {
+    fn progress(task: &Task, tasks: &HashMap<isize, Task>, seen: &mut HashSet<isize>) -> f64 {
+        if task.done {
+            return 1.0;
+        }
+
+        if !seen.insert(task.id) {
+            return task.percent_done;
+        }
+
+        let subtasks = task
+            .related_tasks
+            .as_ref()
+            .and_then(|x| x.get("subtask"))
+            .filter(|x| !x.is_empty());
+
+        let result = subtasks.map_or(task.percent_done, |subtasks| {
+            let total: f64 = subtasks
+                .iter()
+                .map(|sub| progress(tasks.get(&sub.id).unwrap_or(sub), tasks, seen))
+                .sum();
+            total / subtasks.len() as f64
+        });
+
+        seen.remove(&task.id);
+        result
+    }
+
+    progress(task, tasks, &mut HashSet::new())
+}
+
+/// Average progress of the tasks in `projects`.
+///
+/// Subtasks are only counted through their parent task.
+pub fn project_progress(projects: &[isize], tasks: &HashMap<isize, Task>) -> Option<f64> {
+    let in_project: Vec<&Task> = tasks
+        .values()
+        .filter(|x| projects.contains(&x.project_id))
+        .collect();
+
+    let top_level: Vec<&&Task> = in_project
+        .iter()
+        .filter(|x| {
+            !x.related_tasks
+                .as_ref()
+                .and_then(|rel| rel.get("parenttask"))
+                .is_some_and(|parents| {
+                    parents.iter().any(|p| {
+                        tasks
+                            .get(&p.id)
+                            .is_some_and(|p| projects.contains(&p.project_id))
+                    })
+                })
+        })
+        .collect();
+
+    if top_level.is_empty() {
+        return None;
+    }
+
+    let total: f64 = top_level.iter().map(|x| task_progress(x, tasks)).sum();
+    Some(total / top_level.len() as f64)
+}
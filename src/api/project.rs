@@ -1,8 +1,11 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 use super::User;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Project {
     pub id: isize,
     pub title: String,
@@ -21,3 +24,29 @@ pub struct Project {
     pub created: String,
     pub updated: String,
 }
+
+/// Full path of every project, e.g. `Work/Backend`
+pub fn project_paths(projects: &[Project]) -> HashMap<isize, String> {
+    let by_id: HashMap<isize, &Project> = projects.iter().map(|x| (x.id, x)).collect();
+
+    projects
+        .iter()
+        .map(|prj| {
+            let mut path = vec![prj.title.trim()];
+            let mut seen = vec![prj.id];
+            let mut parent = prj.parent_project_id;
+
+            while let Some(p) = by_id.get(&parent) {
+                if seen.contains(&p.id) {
+                    break;
+                }
+                seen.push(p.id);
+                path.push(p.title.trim());
+                parent = p.parent_project_id;
+            }
+
+            path.reverse();
+            (prj.id, path.join("/"))
+        })
+        .collect()
+}
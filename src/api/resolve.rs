@@ -0,0 +1,180 @@
+/// How well an input matches a name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Score {
+    Subsequence,
+    Substring,
+    Prefix,
+}
+
+fn fuzzy_score(input: &str, name: &str) -> Option<Score> {
+    let input = input.to_lowercase();
+    let name = name.to_lowercase();
+
+    if name.starts_with(&input) {
+        return Some(Score::Prefix);
+    }
+
+    if name.contains(&input) {
+        return Some(Score::Substring);
+    }
+
+    let mut chars = name.chars();
+    if input.chars().all(|c| chars.any(|x| x == c)) {
+        return Some(Score::Subsequence);
+    }
+
+    None
+}
+
+/// Maximum amount of candidates listed in an error
+const MAX_CANDIDATES: usize = 10;
+
+fn list_candidates<T>(
+    mut msg: String,
+    candidates: &[&T],
+    display: impl Fn(&T) -> String,
+) -> String {
+    for candidate in candidates.iter().take(MAX_CANDIDATES) {
+        msg.push_str(&format!("\n  {}", display(candidate)));
+    }
+
+    if candidates.len() > MAX_CANDIDATES {
+        msg.push_str(&format!(
+            "\n  ... and {} more",
+            candidates.len() - MAX_CANDIDATES
+        ));
+    }
+
+    msg
+}
+
+/// Resolve `input` to exactly one of `items`.
+///
+/// Every item is known by one or more `names`. Exact matches win over case
+/// insensitive matches. Nothing else is accepted, fuzzy matches are only
+/// suggested in the error, ranked best first.
+pub fn resolve<'a, T>(
+    kind: &str,
+    input: &str,
+    items: &'a [T],
+    names: impl Fn(&T) -> Vec<String>,
+    display: impl Fn(&T) -> String,
+) -> Result<&'a T, String> {
+    let input = input.trim();
+
+    let exact: [fn(&str, &str) -> bool; 2] = [|a, b| a == b, |a, b| a.eq_ignore_ascii_case(b)];
+
+    for matches in exact {
+        let found: Vec<&T> = items
+            .iter()
+            .filter(|x| names(x).iter().any(|name| matches(name.trim(), input)))
+            .collect();
+
+        match found.as_slice() {
+            [] => {}
+            [item] => return Ok(item),
+            _ => {
+                return Err(list_candidates(
+                    format!("{kind} '{input}' is ambiguous, candidates are:"),
+                    &found,
+                    display,
+                ))
+            }
+        }
+    }
+
+    let mut fuzzy: Vec<(Score, &T)> = items
+        .iter()
+        .filter_map(|x| {
+            names(x)
+                .iter()
+                .filter_map(|name| fuzzy_score(input, name.trim()))
+                .max()
+                .map(|score| (score, x))
+        })
+        .collect();
+    fuzzy.sort_by_key(|x| std::cmp::Reverse(x.0));
+
+    let msg = format!("{kind} '{input}' not found");
+    if fuzzy.is_empty() {
+        return Err(msg);
+    }
+
+    let candidates: Vec<&T> = fuzzy.into_iter().map(|x| x.1).collect();
+    Err(list_candidates(
+        format!("{msg}, did you mean:"),
+        &candidates,
+        display,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(input: &str, items: &'a [(usize, &str)]) -> Result<&'a (usize, &'a str), String> {
+        resolve(
+            "Project",
+            input,
+            items,
+            |x| vec![x.1.to_string()],
+            |x| format!("{} [{}]", x.1, x.0),
+        )
+    }
+
+    #[test]
+    fn exact_wins_over_case_insensitive() {
+        let items = [(1, "Work"), (2, "work")];
+
+        assert_eq!(find("work", &items).unwrap().0, 2);
+        assert_eq!(find(" Work ", &items).unwrap().0, 1);
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let items = [(1, "Inbox"), (2, "Work")];
+
+        assert_eq!(find("inbox", &items).unwrap().0, 1);
+    }
+
+    #[test]
+    fn ambiguous() {
+        let items = [(1, "Work"), (2, "WORK"), (3, "Home")];
+
+        assert_eq!(
+            find("work", &items).unwrap_err(),
+            "Project 'work' is ambiguous, candidates are:\n  Work [1]\n  WORK [2]"
+        );
+    }
+
+    #[test]
+    fn fuzzy_only_suggested() {
+        let items = [(1, "Backlog"), (2, "Work/Backend"), (3, "Home")];
+
+        assert_eq!(
+            find("back", &items).unwrap_err(),
+            "Project 'back' not found, did you mean:\n  Backlog [1]\n  Work/Backend [2]"
+        );
+        assert_eq!(
+            find("hme", &items).unwrap_err(),
+            "Project 'hme' not found, did you mean:\n  Home [3]"
+        );
+        assert_eq!(find("xyz", &items).unwrap_err(), "Project 'xyz' not found");
+    }
+
+    #[test]
+    fn suggestions_ranked_and_limited() {
+        let names: Vec<String> = (0..12).map(|i| format!("x{i}ba")).collect();
+        let mut items = vec![(1, "bxa")];
+        items.extend(names.iter().map(|x| (0, x.as_str())));
+        items.push((2, "Bar"));
+
+        let msg = find("ba", &items).unwrap_err();
+        let lines: Vec<&str> = msg.lines().collect();
+
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines[1], "  Bar [2]");
+        assert_eq!(lines[2], "  x0ba [0]");
+        assert_eq!(lines[11], "  ... and 4 more");
+    }
+}
@@ -0,0 +1,204 @@
+use super::{ProjectID, Task, VikunjaAPI};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl Op {
+    fn cmp<T: PartialOrd>(self, a: &T, b: &T) -> bool {
+        match self {
+            Self::Eq => a == b,
+            Self::Ne => a != b,
+            Self::Gt => a > b,
+            Self::Ge => a >= b,
+            Self::Lt => a < b,
+            Self::Le => a <= b,
+        }
+    }
+}
+
+enum Condition {
+    Project(Op, isize),
+    Label(Op, usize),
+    Assignee(Op, String),
+    Done(Op, bool),
+    Favorite(Op, bool),
+    Priority(Op, usize),
+    Due(Op, chrono::DateTime<chrono::Utc>),
+}
+
+/// A task selection like `project=Inbox && label=quick`.
+///
+/// Conditions are joined by `&&` and compare a field with `=`, `!=`, `>`,
+/// `>=`, `<` or `<=`. Supported fields are `project`, `label`, `assignee`,
+/// `done`, `favorite`, `priority` and `due`.
+pub struct TaskSelector {
+    conditions: Vec<Condition>,
+}
+
+fn parse_bool(field: &str, value: &str) -> Result<bool, String> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "1" => Ok(true),
+        "false" | "no" | "0" => Ok(false),
+        _ => Err(format!("'{value}' is no boolean for '{field}'")),
+    }
+}
+
+fn only_equality(field: &str, op: Op) -> Result<(), String> {
+    if matches!(op, Op::Eq | Op::Ne) {
+        Ok(())
+    } else {
+        Err(format!("'{field}' can only be compared with = or !="))
+    }
+}
+
+impl TaskSelector {
+    pub fn parse(api: &VikunjaAPI, expr: &str) -> Result<Self, String> {
+        let mut conditions = Vec::new();
+
+        for part in expr.split("&&").map(str::trim).filter(|x| !x.is_empty()) {
+            let (pos, op, len) = [
+                ("!=", Op::Ne),
+                (">=", Op::Ge),
+                ("<=", Op::Le),
+                ("=", Op::Eq),
+                (">", Op::Gt),
+                ("<", Op::Lt),
+            ]
+            .into_iter()
+            .find_map(|(token, op)| part.find(token).map(|pos| (pos, op, token.len())))
+            .map_or_else(|| Err(format!("'{part}' is no condition")), Ok)?;
+
+            let field = part[..pos].trim().to_lowercase();
+            let value = part[pos + len..].trim().trim_matches(['"', '\'']);
+
+            let condition = match field.as_str() {
+                "project" => {
+                    only_equality(&field, op)?;
+                    Condition::Project(op, ProjectID::parse(api, value)?.0)
+                }
+                "label" | "labels" => {
+                    only_equality(&field, op)?;
+                    Condition::Label(op, api.resolve_label(value)?.id)
+                }
+                "assignee" | "assignees" => {
+                    only_equality(&field, op)?;
+                    Condition::Assignee(op, api.resolve_user(value)?.username)
+                }
+                "done" => {
+                    only_equality(&field, op)?;
+                    Condition::Done(op, parse_bool(&field, value)?)
+                }
+                "favorite" | "fav" => {
+                    only_equality(&field, op)?;
+                    Condition::Favorite(op, parse_bool(&field, value)?)
+                }
+                "priority" => Condition::Priority(
+                    op,
+                    value
+                        .parse()
+                        .map_err(|_| format!("'{value}' is no priority"))?,
+                ),
+                "due" => Condition::Due(
+                    op,
+                    crate::parse_datetime(value)
+                        .map_or_else(|| Err(format!("'{value}' is no date")), Ok)?,
+                ),
+                _ => return Err(format!("Unknown field '{field}'")),
+            };
+
+            conditions.push(condition);
+        }
+
+        if conditions.is_empty() {
+            return Err(String::from("Empty filter"));
+        }
+
+        Ok(Self { conditions })
+    }
+
+    pub fn matches(&self, task: &Task) -> bool {
+        self.conditions.iter().all(|condition| match condition {
+            Condition::Project(op, id) => op.cmp(&task.project_id, id),
+            Condition::Label(op, id) => {
+                let has = task.labels.iter().flatten().any(|x| x.id == *id);
+                op.cmp(&has, &true)
+            }
+            Condition::Assignee(op, username) => {
+                let has = task
+                    .assignees
+                    .iter()
+                    .flatten()
+                    .any(|x| x.username == *username);
+                op.cmp(&has, &true)
+            }
+            Condition::Done(op, done) => op.cmp(&task.done, done),
+            Condition::Favorite(op, fav) => op.cmp(&task.is_favorite, fav),
+            Condition::Priority(op, priority) => op.cmp(&task.priority, priority),
+            Condition::Due(op, date) => chrono::DateTime::parse_from_rfc3339(&task.due_date)
+                .ok()
+                .filter(|x| x.timestamp() > 0)
+                .is_some_and(|due| op.cmp(&due.with_timezone(&chrono::Utc), date)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The fields used here never reach the server
+    fn parse(expr: &str) -> Result<TaskSelector, String> {
+        TaskSelector::parse(&VikunjaAPI::new("http://127.0.0.1:9", ""), expr)
+    }
+
+    fn task(done: bool, priority: usize, due: &str) -> Task {
+        Task {
+            done,
+            priority,
+            due_date: due.to_string(),
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn join_conditions() {
+        let selector = parse("done = false && priority>=3").unwrap();
+
+        assert!(selector.matches(&task(false, 3, "")));
+        assert!(selector.matches(&task(false, 5, "")));
+        assert!(!selector.matches(&task(true, 5, "")));
+        assert!(!selector.matches(&task(false, 2, "")));
+    }
+
+    #[test]
+    fn compare_due_dates() {
+        let selector = parse("due < '2024-06-01' && fav != yes").unwrap();
+
+        assert!(selector.matches(&task(false, 0, "2024-05-31T12:00:00Z")));
+        assert!(!selector.matches(&task(false, 0, "2024-06-02T12:00:00Z")));
+        // Tasks without a due date never match
+        assert!(!selector.matches(&task(false, 0, "0001-01-01T00:00:00Z")));
+    }
+
+    #[test]
+    fn reject_invalid() {
+        for (expr, msg) in [
+            ("", "Empty filter"),
+            (" && ", "Empty filter"),
+            ("done", "'done' is no condition"),
+            ("done > true", "'done' can only be compared with = or !="),
+            ("done = maybe", "'maybe' is no boolean for 'done'"),
+            ("priority = high", "'high' is no priority"),
+            ("size = 3", "Unknown field 'size'"),
+        ] {
+            assert_eq!(parse(expr).err().as_deref(), Some(msg), "{expr}");
+        }
+    }
+}
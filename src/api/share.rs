@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+use super::User;
+
+/// A public link to a project, optionally protected by a password
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkShare {
+    pub id: isize,
+    pub hash: String,
+    pub name: String,
+    pub project_id: isize,
+    pub right: u8,
+    /// `2` if the share needs a password
+    pub sharing_type: u8,
+    pub shared_by: Option<User>,
+    pub expires: Option<String>,
+    pub created: String,
+    pub updated: String,
+}
+
+impl LinkShare {
+    pub const fn has_password(&self) -> bool {
+        self.sharing_type == 2
+    }
+}
@@ -4,7 +4,8 @@ use serde::{Deserialize, Serialize};
 
 use super::{Label, User};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Task {
     pub id: isize,
     pub title: String,
@@ -12,7 +13,7 @@ pub struct Task {
     pub done: bool,
     pub done_at: String,
     pub due_date: String,
-    pub reminders: Option<String>,
+    pub reminders: Option<Vec<Reminder>>,
     pub project_id: isize,
     pub repeat_after: usize,
     pub repeat_mode: usize,
@@ -37,7 +38,31 @@ pub struct Task {
     pub created_by: Option<User>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Task {
+    /// Related tasks of `kind` like `"blocked"`
+    pub fn related(&self, kind: &str) -> &[Task] {
+        self.related_tasks
+            .as_ref()
+            .and_then(|x| x.get(kind))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Tasks blocking this task which are not done yet
+    pub fn open_blockers(&self) -> Vec<&Task> {
+        self.related("blocked").iter().filter(|x| !x.done).collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Reminder {
+    pub reminder: Option<String>,
+    pub relative_period: i64,
+    pub relative_to: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Comment {
     pub id: isize,
     pub author: User,
@@ -46,7 +71,8 @@ pub struct Comment {
     pub updated: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TaskRelation {
     pub created: String,
     pub created_by: User,
@@ -55,6 +81,7 @@ pub struct TaskRelation {
     pub relation_kind: String,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Relation {
     Unknown,
     Subtask,
@@ -70,23 +97,123 @@ pub enum Relation {
     CopiedTo,
 }
 
+/// Accepted names of relation kinds, the API name first
+const RELATION_NAMES: &[(&str, Relation)] = &[
+    ("unknown", Relation::Unknown),
+    ("subtask", Relation::Subtask),
+    ("sub", Relation::Subtask),
+    ("child", Relation::Subtask),
+    ("parenttask", Relation::ParentTask),
+    ("parent", Relation::ParentTask),
+    ("related", Relation::Related),
+    ("relates", Relation::Related),
+    ("duplicateof", Relation::DuplicateOf),
+    ("duplicates", Relation::Duplicates),
+    ("blocking", Relation::Blocking),
+    ("blocks", Relation::Blocking),
+    ("blocked", Relation::Blocked),
+    ("blockedby", Relation::Blocked),
+    ("precedes", Relation::Precedes),
+    ("before", Relation::Precedes),
+    ("follows", Relation::Follows),
+    ("after", Relation::Follows),
+    ("copiedfrom", Relation::CopiedFrom),
+    ("copiedto", Relation::CopiedTo),
+];
+
+/// Edit distance between `a` and `b`
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(cur)
+            };
+            prev = cur;
+        }
+    }
+
+    row[b.len()]
+}
+
 impl Relation {
+    /// Parse a relation kind or one of its aliases like `blocks` or `blocked-by`
     pub fn try_parse(val: &str) -> Option<Self> {
-        match val {
-            "unknown" => Some(Self::Unknown),
-            "subtask" | "sub" => Some(Self::Subtask),
-            "parenttask" | "parent" => Some(Self::ParentTask),
-            "related" => Some(Self::Related),
-            "duplicateof" => Some(Self::DuplicateOf),
-            "duplicates" => Some(Self::Duplicates),
-            "blocking" => Some(Self::Blocking),
-            "blocked" => Some(Self::Blocked),
-            "precedes" => Some(Self::Precedes),
-            "follows" => Some(Self::Follows),
-            "copiedfrom" => Some(Self::CopiedFrom),
-            "copiedto" => Some(Self::CopiedTo),
-            _ => None,
+        let val: String = val
+            .to_lowercase()
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_' | ' '))
+            .collect();
+
+        RELATION_NAMES
+            .iter()
+            .find(|(name, _)| *name == val)
+            .map(|(_, relation)| *relation)
+    }
+
+    /// Like `try_parse`, but suggests the closest relation kind on error
+    pub fn parse(val: &str) -> Result<Self, String> {
+        Self::try_parse(val).map_or_else(
+            || {
+                let input = val.to_lowercase();
+                let closest = RELATION_NAMES
+                    .iter()
+                    .min_by_key(|(name, _)| levenshtein(&input, name))
+                    .map(|(_, relation)| relation.api())
+                    .unwrap();
+
+                Err(format!(
+                    "Unknown relation '{val}', did you mean '{closest}'?\nValid relations: {}",
+                    Self::all()
+                        .iter()
+                        .map(Self::api)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            },
+            Ok,
+        )
+    }
+
+    /// All relation kinds except `unknown`
+    pub fn all() -> Vec<Self> {
+        let mut all: Vec<Self> = Vec::new();
+        for (_, relation) in RELATION_NAMES {
+            if *relation != Self::Unknown && !all.contains(relation) {
+                all.push(*relation);
+            }
         }
+        all
+    }
+
+    /// The relation the other task has to this one
+    pub const fn inverse(&self) -> Option<Self> {
+        match self {
+            Self::Unknown => None,
+            Self::Subtask => Some(Self::ParentTask),
+            Self::ParentTask => Some(Self::Subtask),
+            Self::Related => Some(Self::Related),
+            Self::DuplicateOf => Some(Self::Duplicates),
+            Self::Duplicates => Some(Self::DuplicateOf),
+            Self::Blocking => Some(Self::Blocked),
+            Self::Blocked => Some(Self::Blocking),
+            Self::Precedes => Some(Self::Follows),
+            Self::Follows => Some(Self::Precedes),
+            Self::CopiedFrom => Some(Self::CopiedTo),
+            Self::CopiedTo => Some(Self::CopiedFrom),
+        }
+    }
+
+    /// Human readable name of the relation kind `kind` as returned by the server
+    pub fn repr_kind(kind: &str) -> String {
+        Self::try_parse(kind).map_or_else(|| kind.to_string(), |x| x.repr())
     }
 
     pub fn repr(&self) -> String {
@@ -125,3 +252,34 @@ impl Relation {
         .to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_aliases() {
+        for (input, relation) in [
+            ("subtask", Relation::Subtask),
+            ("child", Relation::Subtask),
+            ("parent", Relation::ParentTask),
+            ("blocks", Relation::Blocking),
+            ("blocked-by", Relation::Blocked),
+            ("Blocked By", Relation::Blocked),
+            ("BLOCKED_BY", Relation::Blocked),
+            ("after", Relation::Follows),
+            ("copied_from", Relation::CopiedFrom),
+        ] {
+            assert_eq!(Relation::try_parse(input), Some(relation), "{input}");
+            assert_eq!(Relation::parse(input), Ok(relation), "{input}");
+        }
+    }
+
+    #[test]
+    fn suggest_closest_relation() {
+        assert_eq!(Relation::try_parse("blokcs"), None);
+
+        let msg = Relation::parse("blokcs").unwrap_err();
+        assert!(msg.starts_with("Unknown relation 'blokcs', did you mean 'blocking'?"));
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+use super::User;
+
+/// Access right of users and teams on a shared project
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Right {
+    Read,
+    Write,
+    Admin,
+}
+
+impl Right {
+    pub fn try_parse(val: &str) -> Option<Self> {
+        match val {
+            "read" => Some(Self::Read),
+            "write" => Some(Self::Write),
+            "admin" => Some(Self::Admin),
+            _ => None,
+        }
+    }
+
+    pub const fn from_api(val: u8) -> Self {
+        match val {
+            0 => Self::Read,
+            1 => Self::Write,
+            _ => Self::Admin,
+        }
+    }
+
+    pub const fn api(self) -> u8 {
+        match self {
+            Self::Read => 0,
+            Self::Write => 1,
+            Self::Admin => 2,
+        }
+    }
+
+    pub fn repr(self) -> String {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Admin => "admin",
+        }
+        .to_string()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TeamMember {
+    #[serde(flatten)]
+    pub user: User,
+    pub admin: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Team {
+    pub id: isize,
+    pub name: String,
+    pub description: String,
+    pub members: Option<Vec<TeamMember>>,
+    pub created: String,
+    pub updated: String,
+}
+
+/// A user a project is shared with
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SharedUser {
+    #[serde(flatten)]
+    pub user: User,
+    pub right: u8,
+}
+
+/// A team a project is shared with
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SharedTeam {
+    #[serde(flatten)]
+    pub team: Team,
+    pub right: u8,
+}
@@ -0,0 +1,68 @@
+use base64::Engine;
+use chrono::{DateTime, Utc};
+
+/// What kind of token vk is authenticated with
+pub enum TokenKind {
+    /// Token of a `vk login`
+    User {
+        expires: Option<DateTime<Utc>>,
+    },
+    /// Token of a link share
+    LinkShare {
+        expires: Option<DateTime<Utc>>,
+    },
+    /// API token created in the Vikunja settings
+    Api,
+    Unknown,
+}
+
+impl TokenKind {
+    /// Inspect the claims of a JWT without verifying it
+    pub fn parse(token: &str) -> Self {
+        if token.starts_with("tk_") {
+            return Self::Api;
+        }
+
+        let claims = token
+            .split('.')
+            .nth(1)
+            .and_then(|x| {
+                base64::engine::general_purpose::URL_SAFE_NO_PAD
+                    .decode(x.trim_end_matches('='))
+                    .ok()
+            })
+            .and_then(|x| serde_json::from_slice::<serde_json::Value>(&x).ok());
+
+        let Some(claims) = claims else {
+            return Self::Unknown;
+        };
+
+        let expires = claims
+            .get("exp")
+            .and_then(serde_json::Value::as_i64)
+            .and_then(|x| DateTime::from_timestamp(x, 0));
+
+        match claims.get("type").and_then(serde_json::Value::as_i64) {
+            Some(1) => Self::User { expires },
+            Some(2) => Self::LinkShare { expires },
+            _ => Self::Unknown,
+        }
+    }
+
+    pub const fn expires(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::User { expires } | Self::LinkShare { expires } => *expires,
+            Self::Api | Self::Unknown => None,
+        }
+    }
+
+    pub fn repr(&self) -> String {
+        match self {
+            Self::User { .. } => "User session",
+            Self::LinkShare { .. } => "Link share",
+            Self::Api => "API token",
+            Self::Unknown => "Unknown",
+        }
+        .to_string()
+    }
+}
@@ -1,23 +1,84 @@
 use clap::{arg, command};
 
+use crate::api::TaskRef;
+
+/// Names of the user settings vk can change
+fn setting_names() -> [&'static str; 7] {
+    crate::ui::user::SETTINGS.map(|(name, _)| name)
+}
+
+/// A task argument accepting `123`, `#123`, `BE-42` or `Backend#42`
+fn task_arg(arg: clap::Arg) -> clap::Arg {
+    arg.required(true)
+        .value_parser(clap::value_parser!(TaskRef))
+}
+
+/// A task argument which is picked interactively if omitted
+fn picked_task_arg(arg: clap::Arg) -> clap::Arg {
+    task_arg(arg).required(false)
+}
+
+/// A project argument which is picked interactively if omitted
+fn project_arg() -> clap::Arg {
+    arg!([project] "Project, picked if omitted").required(false)
+}
+
+/// Task selection of bulk commands by references, `--filter` or stdin
+fn selection_args() -> [clap::Arg; 4] {
+    [
+        task_arg(arg!([task_id] "Tasks, read from stdin or picked if omitted"))
+            .required(false)
+            .num_args(0..),
+        arg!(--filter <filter> "Select tasks like 'project=Inbox && label=quick'")
+            .required(false)
+            .conflicts_with("task_id"),
+        arg!(--"dry-run" "Only show the affected tasks").required(false),
+        arg!(-y --yes "Do not ask for confirmation").required(false),
+    ]
+}
+
+/// Task selection flags shared by the default listing and saved filters
+fn listing_args() -> [clap::Arg; 3] {
+    [
+        arg!(-d --done "Show done tasks too").required(false),
+        arg!(--from <project> "Show only tasks from project").required(false),
+        arg!(-l --label <label> "Show only tasks with label").required(false),
+    ]
+}
+
 pub fn get_args() -> clap::ArgMatches {
     command!()
         .about("CLI Tool for Vikunja")
-        .arg(arg!(-d --done "Show done tasks too").required(false))
+        .args(listing_args())
         .arg(arg!(-f --favorite "Show only favorites").required(false))
-        .arg(arg!(--from <project> "Show only tasks from project").required(false))
-        .arg(arg!(-l --label <label> "Show only tasks with label").required(false))
+        .arg(
+            arg!(-i --identifier "Show task identifiers like BE-42 instead of IDs")
+                .required(false),
+        )
         .subcommand(
             command!()
                 .name("info")
                 .about("Show information on task")
-                .arg(arg!([task_id] "Task ID").required(true)),
+                .arg(picked_task_arg(arg!([task_id] "Task, picked if omitted"))),
         )
         .subcommand(
             command!()
                 .name("prj")
                 .about("Commands about projects")
-                .subcommand(command!().name("ls").about("List projects"))
+                .subcommand(
+                    command!()
+                        .name("ls")
+                        .about("List projects")
+                        .arg(
+                            arg!(--depth <depth> "How many levels of sub projects to show")
+                                .required(false)
+                                .value_parser(clap::value_parser!(usize)),
+                        )
+                        .arg(
+                            arg!(-c --count "Show open and overdue task counts").required(false),
+                        )
+                        .arg(arg!(-a --archived "Show archived projects too").required(false)),
+                )
                 .subcommand(
                     command!()
                         .name("add")
@@ -33,11 +94,230 @@ pub fn get_args() -> clap::ArgMatches {
                         .arg(arg!(-p --parent <parent> "Parent project").required(false))
                         .arg(arg!(<title> "Project title").required(true)),
                 )
+                .subcommand(
+                    command!()
+                        .name("edit")
+                        .about("Edit a project")
+                        .arg(arg!(--title <title> "New project title").required(false))
+                        .arg(
+                            arg!(-d --description <description> "Project description")
+                                .required(false),
+                        )
+                        .arg(
+                            arg!(-c --color <color> "HEX Color Code for the project")
+                                .required(false),
+                        )
+                        .arg(
+                            arg!(-i --identifier <identifier> "Project identifier")
+                                .required(false),
+                        )
+                        .arg(arg!(-p --parent <parent> "Parent project").required(false))
+                        .arg(project_arg()),
+                )
+                .subcommand(
+                    command!()
+                        .name("archive")
+                        .about("Archive a project")
+                        .arg(project_arg()),
+                )
+                .subcommand(
+                    command!()
+                        .name("unarchive")
+                        .about("Unarchive a project")
+                        .arg(project_arg()),
+                )
+                .subcommand(
+                    command!()
+                        .name("mv")
+                        .about("Move a project to another parent")
+                        .arg(
+                            arg!(-p --parent <parent> "New parent project")
+                                .required_unless_present("root"),
+                        )
+                        .arg(
+                            arg!(--root "Make it a top level project")
+                                .required(false)
+                                .conflicts_with("parent"),
+                        )
+                        .arg(project_arg()),
+                )
+                .subcommand(
+                    command!()
+                        .name("dup")
+                        .about("Duplicate a project with its tasks")
+                        .arg(
+                            arg!(-p --parent <parent> "Parent project of the copy")
+                                .required(false),
+                        )
+                        .arg(project_arg()),
+                )
+                .subcommand(
+                    command!()
+                        .name("share")
+                        .about("Share a project with a user or team")
+                        .arg(arg!(--user <user> "User to share with").required(false))
+                        .arg(arg!(--team <team> "Team to share with").required(false))
+                        .group(
+                            clap::ArgGroup::new("target")
+                                .args(["user", "team"])
+                                .required(true),
+                        )
+                        .arg(
+                            arg!(--right <right> "Access right")
+                                .required(false)
+                                .value_parser(["read", "write", "admin"])
+                                .default_value("read"),
+                        )
+                        .arg(project_arg()),
+                )
+                .subcommand(
+                    command!()
+                        .name("link")
+                        .about("Manage public link shares of a project")
+                        .subcommand(
+                            command!()
+                                .name("new")
+                                .about("Create a link share")
+                                .arg(
+                                    arg!(--right <right> "Access right")
+                                        .required(false)
+                                        .value_parser(["read", "write", "admin"])
+                                        .default_value("read"),
+                                )
+                                .arg(
+                                    arg!(--password <password> "Protect the share with a password")
+                                        .required(false),
+                                )
+                                .arg(arg!(--name <name> "Name of the share").required(false))
+                                .arg(project_arg()),
+                        )
+                        .subcommand(
+                            command!()
+                                .name("ls")
+                                .about("List the link shares")
+                                .arg(project_arg()),
+                        )
+                        .subcommand(
+                            command!()
+                                .name("rm")
+                                .about("Remove a link share")
+                                .arg(arg!(-y --yes "Do not ask for confirmation").required(false))
+                                .arg(arg!(<project> "Project").required(true))
+                                .arg(arg!(<share> "Share ID or hash").required(true)),
+                        )
+                        .subcommand_required(true),
+                )
+                .subcommand(
+                    command!()
+                        .name("members")
+                        .about("Show who has access to a project")
+                        .arg(project_arg()),
+                )
                 .subcommand(
                     command!()
                         .name("rm")
-                        .about("Remove a project")
-                        .arg(arg!(<project> "Project").required(true)),
+                        .about("Remove a project with all its tasks")
+                        .arg(arg!(-y --yes "Do not ask for confirmation").required(false))
+                        .arg(project_arg()),
+                ),
+        )
+        .subcommand(
+            command!()
+                .name("tui")
+                .about("Full screen interface to browse and edit tasks"),
+        )
+        .subcommand(
+            command!()
+                .name("doctor")
+                .about("Check config, connection, token and server compatibility"),
+        )
+        .subcommand(
+            command!()
+                .name("whoami")
+                .about("Show the logged in user, host and token"),
+        )
+        .subcommand(
+            command!()
+                .name("settings")
+                .about("Show or change your Vikunja settings")
+                .subcommand(
+                    command!()
+                        .name("get")
+                        .about("Show your settings")
+                        .arg(arg!([key] "Setting").required(false).value_parser(setting_names())),
+                )
+                .subcommand(
+                    command!()
+                        .name("set")
+                        .about("Change a setting")
+                        .arg(arg!(<key> "Setting").required(true).value_parser(setting_names()))
+                        .arg(arg!(<value> "New value").required(true)),
+                ),
+        )
+        .subcommand(
+            command!()
+                .name("team")
+                .about("Manage teams")
+                .subcommand(command!().name("ls").about("List your teams"))
+                .subcommand(
+                    command!()
+                        .name("new")
+                        .about("Create a new team")
+                        .arg(
+                            arg!(-d --description <description> "Team description")
+                                .required(false),
+                        )
+                        .arg(arg!(<name> "Team name").required(true)),
+                )
+                .subcommand(
+                    command!()
+                        .name("add-member")
+                        .about("Add a user to a team")
+                        .arg(arg!(--admin "Make the user a team admin").required(false))
+                        .arg(arg!(<team> "Team").required(true))
+                        .arg(arg!(<user> "User").required(true)),
+                )
+                .subcommand(
+                    command!()
+                        .name("rm-member")
+                        .about("Remove a user from a team")
+                        .arg(arg!(<team> "Team").required(true))
+                        .arg(arg!(<user> "User").required(true)),
+                ),
+        )
+        .subcommand(
+            command!()
+                .name("filter")
+                .about("Manage saved filters")
+                .subcommand(command!().name("ls").about("List saved filters"))
+                .subcommand(
+                    command!()
+                        .name("new")
+                        .about("Create a new saved filter")
+                        .args(listing_args())
+                        .arg(
+                            arg!(--description <description> "Filter description")
+                                .required(false),
+                        )
+                        .arg(arg!(<title> "Filter title").required(true)),
+                )
+                .subcommand(
+                    command!()
+                        .name("edit")
+                        .about("Edit a saved filter. The conditions are replaced if any of them is given")
+                        .args(listing_args())
+                        .arg(arg!(--title <title> "New filter title").required(false))
+                        .arg(
+                            arg!(--description <description> "Filter description")
+                                .required(false),
+                        )
+                        .arg(arg!(<filter> "Saved filter by title or ID").required(true)),
+                )
+                .subcommand(
+                    command!()
+                        .name("rm")
+                        .about("Remove a saved filter")
+                        .arg(arg!(<filter> "Saved filter by title or ID").required(true)),
                 ),
         )
         .subcommand(
@@ -46,69 +326,126 @@ pub fn get_args() -> clap::ArgMatches {
                 .about("Create a new task")
                 .arg(arg!([title] "Task title").required(true))
                 .arg(
-                    arg!(-p --project <project> "Project to add task to")
-                        .required(false)
-                        .default_value("Inbox"),
+                    arg!(-p --project <project> "Project to add task to, your default project or Inbox if omitted")
+                        .required(false),
                 )
                 .arg(arg!(-d --description <description> "Task Description").required(false))
                 .arg(arg!(--due <due> "Task Due").required(false))
-                .arg(arg!(-l --label <label> "Task Label").required(false))
+                .arg(
+                    arg!(-l --label <label> "Task labels, repeatable or comma separated")
+                        .required(false)
+                        .action(clap::ArgAction::Append),
+                )
+                .arg(arg!(--"create-missing" "Create labels which do not exist").required(false))
                 .arg(arg!(--priority <priority> "Task Label").required(false))
-                .arg(arg!(-f --favorite "Mark task as favorite").required(false)),
+                .arg(arg!(-f --favorite "Mark task as favorite").required(false))
+                .arg(
+                    arg!(-a --assign <users> "Assign users by username like 'alice,me'").required(false),
+                ),
         )
         .subcommand(
             command!()
                 .name("login")
                 .about("Get a JWT Token for authentication")
-                .arg(arg!(-u --username <username> "Username").required(true))
-                .arg(arg!(-p --password <password> "Password").required(true))
+                .arg(arg!(-u --username <username> "Username").required_unless_present("link-share"))
+                .arg(
+                    arg!(-p --password <password> "Password, also of password protected link shares")
+                        .required_unless_present("link-share"),
+                )
                 .arg(arg!(--host <host> "Vikunja Host").required(true))
-                .arg(arg!(--totp <totp> "TOTP Code").required(false)),
+                .arg(arg!(--totp <totp> "TOTP Code").required(false))
+                .arg(
+                    arg!(--"link-share" <hash> "Use a link share as read-only profile")
+                        .required(false)
+                        .conflicts_with_all(["username", "totp"]),
+                ),
         )
         .subcommand(
             command!()
                 .name("assign")
                 .about("Assign a user to a task")
                 .arg(arg!(-u --undo "Remove user from task").required(false))
-                .arg(arg!([user] "User").required(true))
-                .arg(arg!([task_id] "Task ID").required(true)),
+                .arg(arg!([user] "Usernames like 'alice,me'").required(true))
+                .args(selection_args()),
         )
         .subcommand(
             command!()
                 .name("comments")
                 .about("Show task comments")
-                .arg(arg!([task_id] "Task ID").required(true)),
+                .arg(picked_task_arg(arg!([task_id] "Task, picked if omitted"))),
         )
         .subcommand(
             command!()
                 .name("comment")
                 .about("Comment on a task")
-                .arg(arg!([task_id] "Task ID").required(true))
-                .arg(arg!([comment] "Comment").required(true)),
+                .subcommand_negates_reqs(true)
+                .args_conflicts_with_subcommands(true)
+                .subcommand(
+                    command!()
+                        .name("edit")
+                        .about("Edit a comment")
+                        .arg(task_arg(arg!([task_id] "Task")))
+                        .arg(
+                            arg!([comment_id] "Comment ID")
+                                .required(true)
+                                .value_parser(clap::value_parser!(isize)),
+                        )
+                        .arg(arg!([comment] "New comment, read from stdin or $EDITOR if omitted").required(false)),
+                )
+                .subcommand(
+                    command!()
+                        .name("rm")
+                        .about("Remove a comment")
+                        .arg(task_arg(arg!([task_id] "Task")))
+                        .arg(
+                            arg!([comment_id] "Comment ID")
+                                .required(true)
+                                .value_parser(clap::value_parser!(isize)),
+                        )
+                        .arg(arg!(-y --yes "Do not ask for confirmation").required(false)),
+                )
+                .arg(picked_task_arg(arg!([task_id] "Task, picked if omitted")))
+                .arg(
+                    arg!([comment] "Comment in Markdown, read from stdin or $EDITOR if omitted")
+                        .required(false),
+                ),
         )
         .subcommand(
             command!()
                 .name("relation")
                 .about("Set task relations")
+                .subcommand_negates_reqs(true)
+                .args_conflicts_with_subcommands(true)
+                .subcommand(
+                    command!()
+                        .name("ls")
+                        .about("List the relations of a task")
+                        .arg(picked_task_arg(arg!([task_id] "Task, picked if omitted"))),
+                )
                 .arg(arg!(-d --delete "Delete the relation").required(false))
-                .arg(arg!([task_id] "Task ID").required(true))
-                .arg(arg!([relation] "Relation").required(true))
-                .arg(arg!([second_task_id] "Other Task ID").required(true)),
+                .arg(task_arg(arg!([task_id] "Task")))
+                .arg(arg!([relation] "Relation like blocks, blocked-by, parent or subtask").required(true))
+                .arg(task_arg(arg!([second_task_id] "Other Task"))),
         )
         .subcommand(
             command!()
                 .name("fav")
                 .about("Favorite a task")
                 .arg(arg!(-u --undo "Remove favorite from task").required(false))
-                .arg(arg!([task_id] "Task ID").required(true)),
+                .args(selection_args()),
         )
         .subcommand(
             command!()
                 .name("label")
-                .about("Add a label to a task")
-                .arg(arg!(-u --undo "Remove label from task").required(false))
-                .arg(arg!([label] "Label").required(true))
-                .arg(arg!([task_id] "Task ID").required(true)),
+                .about("Add or remove labels of tasks")
+                .arg(arg!(-u --undo "Remove the labels from the tasks").required(false))
+                .arg(arg!(--"create-missing" "Create labels which do not exist").required(false))
+                .arg(
+                    arg!([label] "Labels like 'bug,+urgent,-later', prefixed with - to remove them")
+                        .required(true)
+                        .allow_hyphen_values(true),
+                )
+                .args(selection_args()),
         )
         .subcommand(
             command!()
@@ -120,7 +457,7 @@ pub fn get_args() -> clap::ArgMatches {
                         .name("new")
                         .about("Create a new label")
                         .arg(
-                            arg!(-c --color <color> "HEX Color Code for the label").required(false),
+                            arg!(-c --color <color> "HEX Color Code for the label, derived from the title if omitted").required(false),
                         )
                         .arg(
                             arg!(-d --description <description> "Description for the label")
@@ -128,11 +465,30 @@ pub fn get_args() -> clap::ArgMatches {
                         )
                         .arg(arg!(<title> "Label title").required(true)),
                 )
+                .subcommand(
+                    command!()
+                        .name("edit")
+                        .about("Edit a label")
+                        .arg(arg!([label] "Label, picked if omitted").required(false))
+                        .arg(arg!(--title <title> "New title").required(false))
+                        .arg(arg!(-c --color <color> "New HEX color code").required(false))
+                        .arg(
+                            arg!(-d --description <description> "New description")
+                                .required(false),
+                        ),
+                )
+                .subcommand(
+                    command!()
+                        .name("merge")
+                        .about("Move a label on all tasks to another one and remove it")
+                        .arg(arg!(<from> "Label to remove").required(true))
+                        .arg(arg!(<into> "Label to keep").required(true)),
+                )
                 .subcommand(
                     command!()
                         .name("rm")
                         .about("Remove a label")
-                        .arg(arg!(<title> "Label title").required(true)),
+                        .arg(arg!([title] "Label title, picked if omitted").required(false)),
                 ),
         )
         .subcommand(
@@ -140,13 +496,143 @@ pub fn get_args() -> clap::ArgMatches {
                 .name("done")
                 .arg(arg!(-u --undo "Undo completing the task").required(false))
                 .about("Mark task as done")
-                .arg(arg!([task_id] "Task ID").required(true)),
+                .args(selection_args()),
+        )
+        .subcommand(
+            command!()
+                .name("mv")
+                .about("Move tasks to another project")
+                .arg(
+                    arg!(-b --bucket <bucket> "Bucket to move the tasks into")
+                        .required(false)
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(task_arg(arg!([task_id] "Tasks")).num_args(1..))
+                .arg(arg!(<project> "Project").required(true)),
+        )
+        .subcommand(
+            command!()
+                .name("dup")
+                .about("Duplicate tasks")
+                .arg(arg!(-p --project <project> "Project of the copies").required(false))
+                .arg(picked_task_arg(arg!([task_id] "Tasks, picked if omitted")).num_args(1..)),
         )
         .subcommand(
             command!()
                 .name("rm")
                 .about("Remove task")
-                .arg(arg!([task_id] "Task ID").required(true)),
+                .args(selection_args()),
+        )
+        .subcommand(
+            command!()
+                .name("graph")
+                .about("Export the task dependency graph")
+                .arg(arg!(-p --project <project> "Only tasks from project").required(false))
+                .arg(
+                    arg!(--format <format> "Output format")
+                        .required(false)
+                        .value_parser(["dot", "mermaid", "json"])
+                        .default_value("dot"),
+                ),
+        )
+        .subcommand(
+            command!()
+                .name("next")
+                .about("Show what to work on next")
+                .arg(arg!(-p --project <project> "Only tasks from project").required(false))
+                .arg(
+                    arg!(-n --limit <limit> "How many tasks to show")
+                        .required(false)
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("10"),
+                ),
+        )
+        .subcommand(
+            command!()
+                .name("tree")
+                .about("Show a task with all its subtasks")
+                .arg(
+                    arg!(--depth <depth> "How many levels of subtasks to show")
+                        .required(false)
+                        .default_value("10")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(picked_task_arg(arg!([task_id] "Task, picked if omitted"))),
+        )
+        .subcommand(
+            command!()
+                .name("progress")
+                .about("Show or set the progress of a task or show the progress of a project")
+                .arg(
+                    arg!(-p --project <project> "Show the progress of a project")
+                        .required(false)
+                        .conflicts_with("task_id"),
+                )
+                .arg(picked_task_arg(arg!([task_id] "Task, picked if omitted")))
+                .arg(arg!([percent] "New progress like 40%").required(false)),
+        )
+        .subcommand(
+            command!()
+                .name("check")
+                .about("Check checklist items of a task")
+                .arg(task_arg(arg!([task_id] "Task")))
+                .arg(
+                    arg!([item] "Number of the checklist item")
+                        .required(true)
+                        .num_args(1..)
+                        .value_parser(clap::value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            command!()
+                .name("uncheck")
+                .about("Uncheck checklist items of a task")
+                .arg(task_arg(arg!([task_id] "Task")))
+                .arg(
+                    arg!([item] "Number of the checklist item")
+                        .required(true)
+                        .num_args(1..)
+                        .value_parser(clap::value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            command!()
+                .name("checklist")
+                .about("Show and extend the checklist of a task")
+                .subcommand(
+                    command!()
+                        .name("ls")
+                        .about("Show the checklist of a task")
+                        .arg(picked_task_arg(arg!([task_id] "Task, picked if omitted"))),
+                )
+                .subcommand(
+                    command!()
+                        .name("add")
+                        .about("Add an item to the checklist of a task")
+                        .arg(task_arg(arg!([task_id] "Task")))
+                        .arg(arg!([item] "Checklist item").required(true)),
+                ),
+        )
+        .subcommand(
+            command!()
+                .name("trash")
+                .about("Deleted tasks and projects")
+                .subcommand(command!().name("ls").about("List deleted tasks and projects"))
+                .subcommand(
+                    command!()
+                        .name("restore")
+                        .about("Recreate a deleted task or project")
+                        .arg(
+                            arg!(<id> "Trash entry")
+                                .required(true)
+                                .value_parser(clap::value_parser!(usize)),
+                        ),
+                ),
+        )
+        .subcommand(
+            command!()
+                .name("undo")
+                .about("Recreate the last deleted task or project"),
         )
         .get_matches()
 }
@@ -4,4 +4,7 @@ use serde::{Deserialize, Serialize};
 pub struct Config {
     pub host: String,
     pub token: String,
+    /// The token belongs to a link share, so only reading is allowed
+    #[serde(default)]
+    pub read_only: bool,
 }
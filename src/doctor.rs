@@ -0,0 +1,308 @@
+use std::path::Path;
+
+use crossterm::style::Color;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    api::{Feature, Info, Label, Project, Task, TokenKind, VikunjaAPI, VikunjaError},
+    config::Config,
+    ui::print_color,
+};
+
+/// Amount of items of each kind checked against the models
+const SAMPLE_SIZE: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+struct Doctor {
+    failed: bool,
+}
+
+impl Doctor {
+    fn report(&mut self, status: Status, msg: &str) {
+        let (color, tag) = match status {
+            Status::Pass => (Color::Green, "pass"),
+            Status::Warn => (Color::Yellow, "warn"),
+            Status::Fail => (Color::Red, "fail"),
+        };
+
+        self.failed |= status == Status::Fail;
+        print_color(color, &format!("[{tag}] "));
+        println!("{msg}");
+    }
+
+    fn config(&mut self, path: &Path) -> Option<Config> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                self.report(
+                    Status::Fail,
+                    &format!("Config file {}: {e}", path.display()),
+                );
+                return None;
+            }
+        };
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = std::fs::metadata(path).map_or(0, |x| x.permissions().mode());
+            if mode & 0o077 == 0 {
+                self.report(Status::Pass, "Config file is only readable by you");
+            } else {
+                self.report(
+                    Status::Warn,
+                    &format!(
+                        "Config file {} is readable by others, run `chmod 600` on it",
+                        path.display()
+                    ),
+                );
+            }
+        }
+
+        match toml::from_str::<Config>(&content) {
+            Ok(config) => {
+                self.report(Status::Pass, &format!("Config file {}", path.display()));
+                Some(config)
+            }
+            Err(e) => {
+                self.report(Status::Fail, &format!("Config file is invalid: {e}"));
+                None
+            }
+        }
+    }
+
+    /// Check reachability and TLS by fetching `/info`
+    fn server(&mut self, host: &str) -> Option<Info> {
+        let url = format!("{host}/api/v1/info");
+        let client = reqwest::blocking::Client::new();
+
+        let resp = match client.get(&url).send() {
+            Ok(resp) => resp,
+            Err(e) => {
+                let insecure = reqwest::blocking::Client::builder()
+                    .danger_accept_invalid_certs(true)
+                    .build()
+                    .ok()
+                    .and_then(|x| x.get(&url).send().ok());
+
+                if insecure.is_some() {
+                    self.report(
+                        Status::Fail,
+                        &format!("TLS certificate of {host} is invalid"),
+                    );
+                } else {
+                    self.report(Status::Fail, &format!("Host {host} is unreachable: {e}"));
+                }
+                return None;
+            }
+        };
+
+        self.report(Status::Pass, &format!("Host {host} is reachable"));
+
+        if host.starts_with("https://") {
+            self.report(Status::Pass, "TLS certificate is valid");
+        } else {
+            self.report(Status::Warn, "Connection is not encrypted, use https");
+        }
+
+        let info = resp
+            .text()
+            .map_err(|e| e.to_string())
+            .and_then(|x| VikunjaError::parse::<Info>(&x));
+
+        match info {
+            Ok(info) if !info.supports(Feature::Projects) => {
+                self.report(
+                    Status::Fail,
+                    &format!(
+                        "Vikunja {} is too old, vk needs 0.21 or newer",
+                        info.version
+                    ),
+                );
+                Some(info)
+            }
+            Ok(info) if info.version().is_none() => {
+                self.report(
+                    Status::Warn,
+                    &format!("Unknown Vikunja version '{}'", info.version),
+                );
+                Some(info)
+            }
+            Ok(info) => {
+                self.report(Status::Pass, &format!("Vikunja {}", info.version));
+                Some(info)
+            }
+            Err(e) => {
+                self.report(
+                    Status::Fail,
+                    &format!("{url} did not return server information: {e}"),
+                );
+                None
+            }
+        }
+    }
+
+    fn get(&self, config: &Config, path: &str) -> Result<(reqwest::StatusCode, String), String> {
+        let resp = reqwest::blocking::Client::new()
+            .get(format!("{}/api/v1{path}", config.host))
+            .header("Authorization", format!("Bearer {}", config.token))
+            .send()
+            .map_err(|e| e.to_string())?;
+
+        Ok((resp.status(), VikunjaAPI::response_text(resp)))
+    }
+
+    fn token(&mut self, config: &Config) -> bool {
+        let kind = TokenKind::parse(&config.token);
+
+        if let Some(expires) = kind.expires() {
+            let left = expires - chrono::Utc::now();
+            if left < chrono::Duration::zero() {
+                self.report(
+                    Status::Fail,
+                    &format!(
+                        "Token expired {}, run `vk login` again",
+                        expires.date_naive()
+                    ),
+                );
+                return false;
+            } else if left < chrono::Duration::days(7) {
+                self.report(
+                    Status::Warn,
+                    &format!("Token expires soon, on {}", expires.date_naive()),
+                );
+            }
+        }
+
+        let path = if matches!(kind, TokenKind::LinkShare { .. }) {
+            "/projects"
+        } else {
+            "/user"
+        };
+
+        match self.get(config, path) {
+            Ok((status, _)) if status.is_success() => {
+                self.report(Status::Pass, &format!("{} is valid", kind.repr()));
+                true
+            }
+            Ok((status, resp)) => {
+                let msg = VikunjaError::check(&resp)
+                    .err()
+                    .unwrap_or_else(|| status.to_string());
+                self.report(
+                    Status::Fail,
+                    &format!("{} was rejected: {msg}", kind.repr()),
+                );
+                false
+            }
+            Err(e) => {
+                self.report(Status::Fail, &format!("Could not check the token: {e}"));
+                false
+            }
+        }
+    }
+
+    /// Deserialize a sample of `path` into `T` and report the fields which do not fit
+    fn model<T: Default + Serialize + DeserializeOwned>(
+        &mut self,
+        config: &Config,
+        name: &str,
+        path: &str,
+    ) {
+        let items = match self.get(config, path) {
+            Ok((_, resp)) => VikunjaError::parse::<Option<Vec<serde_json::Value>>>(&resp),
+            Err(e) => Err(e),
+        };
+
+        let items = match items {
+            Ok(items) => items.unwrap_or_default(),
+            Err(e) => {
+                self.report(Status::Fail, &format!("Could not fetch {name}: {e}"));
+                return;
+            }
+        };
+
+        let fields: Vec<String> = match serde_json::to_value(T::default()) {
+            Ok(serde_json::Value::Object(obj)) => obj.keys().cloned().collect(),
+            _ => Vec::new(),
+        };
+
+        let mut failed: Vec<String> = Vec::new();
+        let mut missing: Vec<String> = Vec::new();
+
+        for item in items.iter().take(SAMPLE_SIZE) {
+            let Some(obj) = item.as_object() else {
+                continue;
+            };
+
+            if serde_json::from_value::<T>(item.clone()).is_ok() {
+                for field in fields.iter().filter(|x| !obj.contains_key(*x)) {
+                    if !missing.contains(field) {
+                        missing.push(field.clone());
+                    }
+                }
+                continue;
+            }
+
+            // Models default missing fields, so each field can be tried on its own
+            for (key, value) in obj {
+                let single = serde_json::json!({ key: value });
+                if let Err(e) = serde_json::from_value::<T>(single) {
+                    let entry = format!("{key} ({e})");
+                    if !failed.contains(&entry) {
+                        failed.push(entry);
+                    }
+                }
+            }
+        }
+
+        let checked = items.len().min(SAMPLE_SIZE);
+
+        if !failed.is_empty() {
+            self.report(
+                Status::Fail,
+                &format!("{name} do not match the model: {}", failed.join(", ")),
+            );
+        } else if !missing.is_empty() {
+            self.report(
+                Status::Warn,
+                &format!(
+                    "{checked} {name} read, but the server does not send: {}",
+                    missing.join(", ")
+                ),
+            );
+        } else {
+            self.report(Status::Pass, &format!("{checked} {name} read"));
+        }
+    }
+}
+
+/// Check config, connection, token and models and print one line per check.
+///
+/// Returns `false` if any check failed.
+pub fn run(config_path: &Path) -> bool {
+    let mut doctor = Doctor { failed: false };
+
+    let Some(config) = doctor.config(config_path) else {
+        return false;
+    };
+
+    if doctor.server(&config.host).is_some() && doctor.token(&config) {
+        doctor.model::<Task>(
+            &config,
+            "Tasks",
+            &format!("/tasks/all?per_page={SAMPLE_SIZE}"),
+        );
+        doctor.model::<Project>(&config, "Projects", "/projects");
+        doctor.model::<Label>(&config, "Labels", "/labels");
+    }
+
+    !doctor.failed
+}
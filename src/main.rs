@@ -1,11 +1,17 @@
 mod api;
 mod args;
 mod config;
+mod doctor;
+mod trash;
+mod tui;
 mod ui;
 
-use std::path::PathBuf;
+use std::{io::IsTerminal, path::PathBuf};
 
-use api::{ProjectID, Relation, VikunjaAPI};
+use api::{
+    checklist, resolve, Feature, FilterQuery, Label, LabelChanges, ProjectID, Relation, Right,
+    Task, TaskRef, TaskSelector, VikunjaAPI,
+};
 use clap::ArgMatches;
 use once_cell::sync::Lazy;
 use ui::{hex_to_color, print_color};
@@ -15,8 +21,7 @@ static CONFIG_PATH: Lazy<PathBuf> =
 
 fn login_cmd(arg: &ArgMatches) {
     if let Some(("login", login_arg)) = arg.subcommand() {
-        let username: &String = login_arg.get_one("username").unwrap();
-        let password: &String = login_arg.get_one("password").unwrap();
+        let password: Option<&String> = login_arg.get_one("password");
         let totp: Option<&String> = login_arg.get_one("totp");
         let host: &String = login_arg.get_one("host").unwrap();
 
@@ -28,15 +33,233 @@ fn login_cmd(arg: &ArgMatches) {
 
         let api = VikunjaAPI::new(&host, "");
 
-        let token = api.login(username, password, totp.map(std::string::String::as_str));
-        let config = format!("host = \"{host}\"\ntoken = \"{token}\"");
+        let config = if let Some(hash) = login_arg.get_one::<String>("link-share") {
+            let token = api
+                .link_share_auth(hash, password.map(std::string::String::as_str))
+                .unwrap_or_else(|msg| exit_with_error(&msg));
+            format!("host = \"{host}\"\ntoken = \"{token}\"\nread_only = true")
+        } else {
+            let username: &String = login_arg.get_one("username").unwrap();
+            let token = api.login(
+                username,
+                password.unwrap(),
+                totp.map(std::string::String::as_str),
+            );
+            format!("host = \"{host}\"\ntoken = \"{token}\"")
+        };
 
         std::fs::write(CONFIG_PATH.clone(), config).unwrap();
         std::process::exit(0);
     }
 }
 
-fn project_commands(arg: &ArgMatches, api: &VikunjaAPI) {
+/// Whether the command only reads, which is all a link share profile may do
+fn is_read_only(arg: &ArgMatches) -> bool {
+    match arg.subcommand() {
+        Some(("info" | "comments" | "tree" | "next" | "graph" | "whoami" | "tui", _)) | None => {
+            true
+        }
+        Some(("settings", settings_arg)) => settings_arg.subcommand_name() != Some("set"),
+        Some(("progress", progress_arg)) => !progress_arg.contains_id("percent"),
+        Some(("relation", relation_arg)) => relation_arg.subcommand_name() == Some("ls"),
+        Some(("prj", prj_arg)) => match prj_arg.subcommand() {
+            Some(("link", link_arg)) => link_arg.subcommand_name() == Some("ls"),
+            sub => matches!(sub, None | Some(("ls" | "members", _))),
+        },
+        Some(("filter" | "labels" | "team" | "checklist" | "trash", sub_arg)) => {
+            matches!(sub_arg.subcommand_name(), None | Some("ls"))
+        }
+        _ => false,
+    }
+}
+
+fn exit_with_error(msg: &str) -> ! {
+    print_color(crossterm::style::Color::Red, msg);
+    println!();
+    std::process::exit(1);
+}
+
+/// Resolve the task argument `name` to a task ID
+///
+/// Lets the user pick a task if the argument is omitted.
+fn get_task_id(arg: &ArgMatches, name: &str, api: &VikunjaAPI) -> isize {
+    match arg.get_one::<TaskRef>(name) {
+        Some(task) => task.resolve(api),
+        None => ui::picker::pick_tasks(api, false).map(|x| x[0].id),
+    }
+    .unwrap_or_else(|msg| exit_with_error(&msg))
+}
+
+/// Resolve the task arguments `name` to task IDs
+///
+/// Lets the user pick tasks if the argument is omitted.
+fn get_task_ids(arg: &ArgMatches, name: &str, api: &VikunjaAPI) -> Vec<isize> {
+    let Some(tasks) = arg.get_many::<TaskRef>(name) else {
+        return ui::picker::pick_tasks(api, true)
+            .unwrap_or_else(|msg| exit_with_error(&msg))
+            .iter()
+            .map(|x| x.id)
+            .collect();
+    };
+
+    tasks
+        .map(|task| {
+            task.resolve(api)
+                .unwrap_or_else(|msg| exit_with_error(&msg))
+        })
+        .collect()
+}
+
+/// Resolve the `project` argument, letting the user pick one if it is omitted
+fn get_project(arg: &ArgMatches, api: &VikunjaAPI) -> Result<ProjectID, String> {
+    match arg.get_one::<String>("project") {
+        Some(project) => ProjectID::parse(api, project),
+        None => ui::picker::pick_project(api, false, |_| true),
+    }
+}
+
+/// Like `get_project`, but for an archived project
+fn get_archived_project(arg: &ArgMatches, api: &VikunjaAPI) -> Result<ProjectID, String> {
+    match arg.get_one::<String>("project") {
+        Some(project) => ProjectID::parse_archived(api, project),
+        None => ui::picker::pick_project(api, true, |x| x.is_archived),
+    }
+}
+
+/// Tasks selected by references, `--filter`, references read from stdin or
+/// picked by the user
+fn select_tasks(arg: &ArgMatches, api: &VikunjaAPI) -> Result<Vec<Task>, String> {
+    if let Some(filter) = arg.get_one::<String>("filter") {
+        let selector = TaskSelector::parse(api, filter)?;
+        return Ok(api
+            .get_all_tasks()
+            .into_iter()
+            .filter(|x| selector.matches(x))
+            .collect());
+    }
+
+    let refs: Vec<TaskRef> = if let Some(refs) = arg.get_many::<TaskRef>("task_id") {
+        refs.cloned().collect()
+    } else if std::io::stdin().is_terminal() {
+        return ui::picker::pick_tasks(api, true);
+    } else {
+        std::io::read_to_string(std::io::stdin())
+            .map_err(|e| e.to_string())?
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|x| !x.is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()?
+    };
+
+    refs.iter()
+        .map(|task| {
+            let id = task.resolve(api)?;
+            api.get_task(id)
+                .map_err(|()| format!("Could not get task #{id}"))
+        })
+        .collect()
+}
+
+/// Preview the selected tasks and ask before changing more than one of them.
+///
+/// `warn` prints warnings about the tasks and returns whether there were any,
+/// which makes asking necessary even for a single task. Returns `false` if
+/// nothing should be changed.
+fn confirm_selection(
+    arg: &ArgMatches,
+    api: &VikunjaAPI,
+    tasks: &[Task],
+    action: &str,
+    always: bool,
+    warn: impl Fn(&[Task]) -> bool,
+) -> bool {
+    if tasks.is_empty() {
+        println!("No tasks selected");
+        return false;
+    }
+
+    let dry_run = arg.get_flag("dry-run");
+
+    if dry_run || always || tasks.len() > 1 {
+        let projects = api.get_all_projects();
+        for task in tasks {
+            ui::task::print_task_oneline(task, &projects, false);
+        }
+    }
+
+    let warned = warn(tasks);
+
+    if dry_run {
+        println!("{} would be affected", ui::plural(tasks.len(), "task"));
+        return false;
+    }
+
+    let question = format!(
+        "{action} {}{}?",
+        ui::plural(tasks.len(), "task"),
+        if warned { " anyway" } else { "" }
+    );
+    (tasks.len() == 1 && !always && !warned) || arg.get_flag("yes") || ui::confirm(&question)
+}
+
+/// Run `f` on the selected tasks after confirmation and show the result.
+///
+/// `warn` is passed on to `confirm_selection`. Returns the changed tasks as
+/// they were before the change.
+fn bulk_command<F>(
+    arg: &ArgMatches,
+    api: &VikunjaAPI,
+    action: &str,
+    info: bool,
+    warn: impl Fn(&[Task]) -> bool,
+    f: F,
+) -> Vec<Task>
+where
+    F: Fn(&[Task]) -> Result<(), String>,
+{
+    let tasks = select_tasks(arg, api).unwrap_or_else(|msg| exit_with_error(&msg));
+
+    if !confirm_selection(arg, api, &tasks, action, false, warn) {
+        return Vec::new();
+    }
+
+    if let Err(msg) = f(&tasks) {
+        exit_with_error(&msg);
+    }
+
+    match tasks.as_slice() {
+        [task] if info => ui::task::print_task_info(task.id, api),
+        [_] => {}
+        _ => println!("{action}: {}", ui::plural(tasks.len(), "task")),
+    }
+
+    tasks
+}
+
+fn check_color(color: &str) -> Result<(), String> {
+    hex_to_color(color).map_or_else(|_| Err(format!("'{color}' is no hex color")), |_| Ok(()))
+}
+
+/// Fail if the project `id` can not be placed below the project `parent`,
+/// which must neither be the project itself nor one of its sub projects
+fn check_parent(api: &VikunjaAPI, id: isize, parent: isize) -> Result<(), String> {
+    if parent == id {
+        return Err(String::from("A project can not be its own parent"));
+    }
+
+    let subtree = ui::project::project_subtree(id, &api.get_projects(true));
+    if subtree.contains(&parent) {
+        return Err(String::from(
+            "A project can not be moved below one of its sub projects",
+        ));
+    }
+
+    Ok(())
+}
+
+fn project_commands(arg: &ArgMatches, api: &VikunjaAPI) -> Result<(), String> {
+    api.require(Feature::Projects)?;
+
     match arg.subcommand() {
         Some(("add", add_prj_arg)) => {
             let title: &String = add_prj_arg.get_one("title").unwrap();
@@ -47,25 +270,439 @@ fn project_commands(arg: &ArgMatches, api: &VikunjaAPI) {
                 title,
                 description.map(std::string::String::as_str),
                 color.map(std::string::String::as_str),
-                parent.map(|x| ProjectID::parse(api, x).unwrap()),
+                parent.map(|x| ProjectID::parse(api, x)).transpose()?,
             );
         }
+        Some(("edit", edit_prj_arg)) => {
+            let mut project = api.get_project(&get_project(edit_prj_arg, api)?);
+
+            if let Some(title) = edit_prj_arg.get_one::<String>("title") {
+                project.title.clone_from(title);
+            }
+            if let Some(description) = edit_prj_arg.get_one::<String>("description") {
+                project.description.clone_from(description);
+            }
+            if let Some(color) = edit_prj_arg.get_one::<String>("color") {
+                check_color(color)?;
+                project.hex_color = color.trim_start_matches('#').to_string();
+            }
+            if let Some(identifier) = edit_prj_arg.get_one::<String>("identifier") {
+                project.identifier.clone_from(identifier);
+            }
+            if let Some(parent) = edit_prj_arg.get_one::<String>("parent") {
+                project.parent_project_id = ProjectID::parse(api, parent)?.0;
+                check_parent(api, project.id, project.parent_project_id)?;
+            }
+
+            api.update_project(&project)?;
+        }
+        Some(("archive", archive_prj_arg)) => {
+            api.archive_project(&get_project(archive_prj_arg, api)?, true)?;
+        }
+        Some(("unarchive", archive_prj_arg)) => {
+            api.archive_project(&get_archived_project(archive_prj_arg, api)?, false)?;
+        }
+        Some(("mv", mv_prj_arg)) => {
+            let mut project = api.get_project(&get_project(mv_prj_arg, api)?);
+
+            project.parent_project_id = match mv_prj_arg.get_one::<String>("parent") {
+                Some(parent) => ProjectID::parse(api, parent)?.0,
+                None => 0,
+            };
+
+            check_parent(api, project.id, project.parent_project_id)?;
+
+            api.update_project(&project)?;
+        }
+        Some(("dup", dup_prj_arg)) => {
+            let parent: Option<&String> = dup_prj_arg.get_one("parent");
+            let parent = parent.map(|x| ProjectID::parse(api, x)).transpose()?;
+
+            let project = api.duplicate_project(&get_project(dup_prj_arg, api)?, parent)?;
+            println!("Duplicated as '{}' [{}]", project.title, project.id);
+        }
         Some(("rm", rm_prj_arg)) => {
-            let prj: &String = rm_prj_arg.get_one("project").unwrap();
-            api.delete_project(&ProjectID::parse(api, prj).unwrap());
+            let project = get_project(rm_prj_arg, api)?;
+            let (title, tasks, projects) = trash::project_summary(api, &project)?;
+
+            if !rm_prj_arg.get_flag("yes")
+                && !ui::confirm(&format!(
+                    "Delete project '{title}' with {} and {}?",
+                    ui::plural(tasks, "task"),
+                    ui::plural(projects, "sub project")
+                ))
+            {
+                return Ok(());
+            }
+
+            let snapshot = trash::snapshot_project(api, &project)?;
+            let entry = trash::save(trash::TrashItem::Project(snapshot))?;
+            api.delete_project(&project);
+            println!("Deleted, restore it with `vk trash restore {}`", entry.id);
+        }
+        Some(("share", share_prj_arg)) => {
+            let project = get_project(share_prj_arg, api)?;
+            let right: &String = share_prj_arg.get_one("right").unwrap();
+            let right = Right::try_parse(right).unwrap();
+
+            if let Some(user) = share_prj_arg.get_one::<String>("user") {
+                let user = api.resolve_user(user)?;
+                api.share_project_with_user(&project, &user, right)?;
+            }
+            if let Some(team) = share_prj_arg.get_one::<String>("team") {
+                let team = api.resolve_team(team)?;
+                api.share_project_with_team(&project, &team, right)?;
+            }
+        }
+        Some(("link", link_prj_arg)) => link_share_commands(link_prj_arg, api)?,
+        Some(("members", members_prj_arg)) => {
+            ui::team::print_project_members(api, &get_project(members_prj_arg, api)?);
+        }
+        Some(("ls", ls_prj_arg)) => {
+            let depth: Option<&usize> = ls_prj_arg.get_one("depth");
+            let count = ls_prj_arg.get_flag("count");
+            let archived = ls_prj_arg.get_flag("archived");
+            ui::project::list_projects(api, depth.copied(), count, archived);
+        }
+        _ => {
+            ui::project::list_projects(api, None, false, false);
+        }
+    }
+
+    Ok(())
+}
+
+fn link_share_commands(arg: &ArgMatches, api: &VikunjaAPI) -> Result<(), String> {
+    api.require(Feature::LinkShares)?;
+
+    match arg.subcommand() {
+        Some(("new", new_link_arg)) => {
+            let project = get_project(new_link_arg, api)?;
+            let right: &String = new_link_arg.get_one("right").unwrap();
+            let name: Option<&String> = new_link_arg.get_one("name");
+            let password: Option<&String> = new_link_arg.get_one("password");
+
+            let share = api.new_link_share(
+                &project,
+                Right::try_parse(right).unwrap(),
+                name.map(std::string::String::as_str),
+                password.map(std::string::String::as_str),
+            )?;
+            println!("{}", api.link_share_url(&share));
+        }
+        Some(("rm", rm_link_arg)) => {
+            let prj: &String = rm_link_arg.get_one("project").unwrap();
+            let project = ProjectID::parse(api, prj)?;
+            let share: &String = rm_link_arg.get_one("share").unwrap();
+            let share = api.resolve_link_share(&project, share)?;
+
+            if rm_link_arg.get_flag("yes")
+                || ui::confirm(&format!(
+                    "Remove link share {}? It stops working for everyone using it.",
+                    api.link_share_url(&share)
+                ))
+            {
+                api.delete_link_share(&project, &share)?;
+            }
+        }
+        Some(("ls", ls_link_arg)) => {
+            ui::team::print_link_shares(api, &get_project(ls_link_arg, api)?);
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn settings_commands(arg: &ArgMatches, api: &VikunjaAPI) -> Result<(), String> {
+    let mut settings = api.get_user_settings()?;
+
+    match arg.subcommand() {
+        Some(("set", set_arg)) => {
+            let key: &String = set_arg.get_one("key").unwrap();
+            let value: &String = set_arg.get_one("value").unwrap();
+            let (_, api_key) = ui::user::SETTINGS
+                .into_iter()
+                .find(|(name, _)| name == key)
+                .unwrap();
+
+            settings[api_key] = match key.as_str() {
+                "default_project" => {
+                    let project = ProjectID::parse(api, value)?;
+                    if project.saved_filter_id().is_some() || project.0 < 0 {
+                        return Err(format!("'{value}' is not a project"));
+                    }
+                    serde_json::json!(project.0)
+                }
+                "week_start" => {
+                    let day = ui::user::WEEKDAYS
+                        .iter()
+                        .position(|x| x.starts_with(&value.to_lowercase()) && value.len() >= 2)
+                        .or_else(|| value.parse().ok().filter(|x| *x < 7))
+                        .map_or_else(|| Err(format!("'{value}' is no weekday")), Ok)?;
+                    serde_json::json!(day)
+                }
+                "email_reminders" | "overdue_reminders" => match value.as_str() {
+                    "true" | "on" | "yes" => serde_json::json!(true),
+                    "false" | "off" | "no" => serde_json::json!(false),
+                    _ => return Err(format!("'{value}' is neither true nor false")),
+                },
+                _ => serde_json::json!(value),
+            };
+
+            api.update_user_settings(&settings)?;
+            ui::user::print_settings(api, &settings, Some(key))?;
+        }
+        Some(("get", get_arg)) => {
+            let key: Option<&String> = get_arg.get_one("key");
+            ui::user::print_settings(api, &settings, key.map(std::string::String::as_str))?;
+        }
+        _ => ui::user::print_settings(api, &settings, None)?,
+    }
+
+    Ok(())
+}
+
+fn team_commands(arg: &ArgMatches, api: &VikunjaAPI) -> Result<(), String> {
+    match arg.subcommand() {
+        Some(("new", new_team_arg)) => {
+            let name: &String = new_team_arg.get_one("name").unwrap();
+            let description: Option<&String> = new_team_arg.get_one("description");
+
+            let team = api.new_team(name, description.map(std::string::String::as_str))?;
+            println!("Created team '{}' [{}]", team.name, team.id);
+        }
+        Some(("add-member", member_arg)) => {
+            let team: &String = member_arg.get_one("team").unwrap();
+            let user: &String = member_arg.get_one("user").unwrap();
+
+            api.add_team_member(
+                &api.resolve_team(team)?,
+                &api.resolve_user(user)?,
+                member_arg.get_flag("admin"),
+            )?;
+        }
+        Some(("rm-member", member_arg)) => {
+            let team: &String = member_arg.get_one("team").unwrap();
+            let user: &String = member_arg.get_one("user").unwrap();
+
+            api.remove_team_member(&api.resolve_team(team)?, &api.resolve_user(user)?)?;
         }
         _ => {
-            ui::project::list_projects(api);
+            ui::team::list_teams(api);
+        }
+    }
+
+    Ok(())
+}
+
+/// Build a saved filter query from the listing flags
+fn filter_query(arg: &ArgMatches, api: &VikunjaAPI) -> Result<FilterQuery, String> {
+    let done = arg.get_flag("done");
+    let project: Option<&String> = arg.get_one("from");
+    let label: Option<&String> = arg.get_one("label");
+
+    let project = if let Some(project) = project {
+        let p_id = ProjectID::parse(api, project)?;
+        if p_id.saved_filter_id().is_some() {
+            return Err(format!("'{project}' is a saved filter and not a project"));
+        }
+        Some(p_id.0)
+    } else {
+        None
+    };
+
+    let label = if let Some(label) = label {
+        Some(api.resolve_label(label)?.id)
+    } else {
+        None
+    };
+
+    Ok(FilterQuery::new(done, project, label))
+}
+
+/// Saved filter by the ID `vk filter ls` shows, its project ID or its title
+fn saved_filter_id(api: &VikunjaAPI, filter: &str) -> Result<isize, String> {
+    if let Ok(id) = filter.trim_start_matches('#').parse::<isize>() {
+        if id > 0 {
+            return Ok(id);
         }
     }
+
+    ProjectID::parse(api, filter)?
+        .saved_filter_id()
+        .map_or_else(|| Err(format!("'{filter}' is not a saved filter")), Ok)
 }
 
-fn label_commands(arg: &ArgMatches, api: &VikunjaAPI) {
+fn filter_commands(arg: &ArgMatches, api: &VikunjaAPI) -> Result<(), String> {
+    api.require(Feature::Projects)?;
+
+    match arg.subcommand() {
+        Some(("new", new_filter_arg)) => {
+            let title: &String = new_filter_arg.get_one("title").unwrap();
+            let description: Option<&String> = new_filter_arg.get_one("description");
+            let query = filter_query(new_filter_arg, api)?;
+
+            let filter =
+                api.new_saved_filter(title, description.map(std::string::String::as_str), &query)?;
+            println!(
+                "Created saved filter '{}' (use it with `vk --from {}`)",
+                filter.title,
+                ProjectID::from_saved_filter(filter.id).0
+            );
+        }
+        Some(("edit", edit_filter_arg)) => {
+            let filter: &String = edit_filter_arg.get_one("filter").unwrap();
+            let mut filter = api.get_saved_filter(saved_filter_id(api, filter)?)?;
+
+            if let Some(title) = edit_filter_arg.get_one::<String>("title") {
+                filter.title.clone_from(title);
+            }
+            if let Some(description) = edit_filter_arg.get_one::<String>("description") {
+                filter.description.clone_from(description);
+            }
+            if edit_filter_arg.get_flag("done")
+                || edit_filter_arg.contains_id("from")
+                || edit_filter_arg.contains_id("label")
+            {
+                filter.filters = filter_query(edit_filter_arg, api)?;
+            }
+
+            api.update_saved_filter(&filter)?;
+        }
+        Some(("rm", rm_filter_arg)) => {
+            let filter: &String = rm_filter_arg.get_one("filter").unwrap();
+            api.delete_saved_filter(saved_filter_id(api, filter)?);
+        }
+        _ => {
+            ui::filter::list_filters(api);
+        }
+    }
+
+    Ok(())
+}
+
+/// Items of a comma separated list
+fn split_list(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',').map(str::trim).filter(|x| !x.is_empty())
+}
+
+/// Resolve label names or IDs, creating the ones no label is titled like if `create_missing`.
+///
+/// Titles have to match exactly, ignoring case, so a typo is an error instead
+/// of picking a similar label.
+fn resolve_labels(
+    api: &VikunjaAPI,
+    names: &[String],
+    create_missing: bool,
+) -> Result<Vec<Label>, String> {
+    let (mut labels, missing) = find_labels(api, names, create_missing)?;
+    labels.extend(create_labels(api, &missing));
+    Ok(labels)
+}
+
+/// Resolve label names or IDs without creating any, returning the names no
+/// label is titled like separately if `allow_missing`
+fn find_labels(
+    api: &VikunjaAPI,
+    names: &[String],
+    allow_missing: bool,
+) -> Result<(Vec<Label>, Vec<String>), String> {
+    let existing = api.get_all_labels();
+    let mut labels = Vec::new();
+    let mut missing: Vec<String> = Vec::new();
+
+    for name in names {
+        let is_missing = !existing.iter().any(|x| {
+            x.title.trim().eq_ignore_ascii_case(name)
+                || name.trim_start_matches('#').parse() == Ok(x.id)
+        });
+
+        if allow_missing && is_missing {
+            if !missing.iter().any(|x| x.eq_ignore_ascii_case(name)) {
+                missing.push(name.clone());
+            }
+        } else {
+            labels.push(VikunjaAPI::resolve_label_in(name, &existing)?);
+        }
+    }
+
+    Ok((labels, missing))
+}
+
+/// Create labels titled `names` with their automatic color
+fn create_labels(api: &VikunjaAPI, names: &[String]) -> Vec<Label> {
+    names
+        .iter()
+        .map(|name| api.new_label(name, None, Some(&ui::auto_color(name))))
+        .collect()
+}
+
+/// Comment text from the `comment` argument, stdin or the editor as HTML
+fn comment_input(arg: &ArgMatches, initial: &str) -> Result<String, String> {
+    let text = if let Some(comment) = arg.get_one::<String>("comment") {
+        comment.clone()
+    } else if std::io::stdin().is_terminal() {
+        ui::edit_in_editor(initial)?
+    } else {
+        std::io::read_to_string(std::io::stdin()).map_err(|e| e.to_string())?
+    };
+
+    if text.trim().is_empty() {
+        return Err(String::from("Empty comment, aborting"));
+    }
+
+    Ok(ui::markdown_to_html(&text))
+}
+
+fn comment_commands(arg: &ArgMatches, api: &VikunjaAPI) -> Result<(), String> {
+    api.require(Feature::Comments)?;
+
+    match arg.subcommand() {
+        Some(("edit", edit_arg)) => {
+            let task_id = get_task_id(edit_arg, "task_id", api);
+            let comment_id: &isize = edit_arg.get_one("comment_id").unwrap();
+
+            let comment = api.get_task_comment(task_id, *comment_id)?;
+            let text = comment_input(
+                edit_arg,
+                &html2text::from_read(comment.comment.as_bytes(), 80),
+            )?;
+
+            let comment = api.update_comment(task_id, *comment_id, &text)?;
+            ui::task::print_comment(&comment);
+        }
+        Some(("rm", rm_arg)) => {
+            let task_id = get_task_id(rm_arg, "task_id", api);
+            let comment_id: &isize = rm_arg.get_one("comment_id").unwrap();
+
+            let comment = api.get_task_comment(task_id, *comment_id)?;
+            ui::task::print_comment(&comment);
+
+            if rm_arg.get_flag("yes") || ui::confirm("Remove this comment?") {
+                api.delete_comment(task_id, *comment_id)?;
+            }
+        }
+        _ => {
+            let task_id = get_task_id(arg, "task_id", api);
+            let text = comment_input(arg, "")?;
+
+            api.new_comment(task_id, &text)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn label_commands(arg: &ArgMatches, api: &VikunjaAPI) -> Result<(), String> {
     match arg.subcommand() {
         Some(("rm", rm_label_arg)) => {
-            let title: &String = rm_label_arg.get_one("title").unwrap();
+            let title = match rm_label_arg.get_one::<String>("title") {
+                Some(title) => title.clone(),
+                None => ui::picker::pick_label(api)?.title,
+            };
 
-            api.remove_label(title);
+            api.remove_label(&title)?;
         }
         Some(("new", new_label_arg)) => {
             let description: Option<&String> = new_label_arg.get_one("description");
@@ -73,26 +710,52 @@ fn label_commands(arg: &ArgMatches, api: &VikunjaAPI) {
             let title: &String = new_label_arg.get_one("title").unwrap();
 
             if let Some(color) = color {
-                if hex_to_color(color).is_err() {
-                    print_color(
-                        crossterm::style::Color::Red,
-                        &format!("'{color}' is no hex color"),
-                    );
-                    println!();
-                    std::process::exit(1);
-                }
+                check_color(color)?;
             }
 
             api.new_label(
                 title.as_str(),
                 description.map(std::string::String::as_str),
-                color.map(std::string::String::as_str),
+                Some(&color.cloned().unwrap_or_else(|| ui::auto_color(title))),
+            );
+        }
+        Some(("edit", edit_label_arg)) => {
+            let mut label = match edit_label_arg.get_one::<String>("label") {
+                Some(label) => api.resolve_label(label)?,
+                None => ui::picker::pick_label(api)?,
+            };
+
+            if let Some(title) = edit_label_arg.get_one::<String>("title") {
+                label.title.clone_from(title);
+            }
+            if let Some(color) = edit_label_arg.get_one::<String>("color") {
+                check_color(color)?;
+                label.hex_color = color.trim_start_matches('#').to_string();
+            }
+            if let Some(description) = edit_label_arg.get_one::<String>("description") {
+                label.description.clone_from(description);
+            }
+
+            api.update_label(&label)?;
+        }
+        Some(("merge", merge_label_arg)) => {
+            let from: &String = merge_label_arg.get_one("from").unwrap();
+            let into: &String = merge_label_arg.get_one("into").unwrap();
+            let from = api.resolve_label(from)?;
+            let into = api.resolve_label(into)?;
+
+            let count = api.merge_labels(&from, &into)?;
+            println!(
+                "Merged '{}' into '{}' on {count} tasks",
+                from.title, into.title
             );
         }
         _ => {
             ui::print_all_labels(api);
         }
     }
+
+    Ok(())
 }
 
 fn load_config() -> config::Config {
@@ -150,64 +813,307 @@ fn main() {
 
     login_cmd(&arg);
 
+    // Runs without a working config to find out what is wrong with it
+    if arg.subcommand_name() == Some("doctor") {
+        std::process::exit(i32::from(!doctor::run(&CONFIG_PATH)));
+    }
+
     let config = load_config();
     let api = VikunjaAPI::new(&config.host, &config.token);
 
+    if config.read_only && !is_read_only(&arg) {
+        exit_with_error("This profile uses a link share and is read-only");
+    }
+
     match arg.subcommand() {
+        Some(("tui", _)) => {
+            if let Err(msg) = tui::run(&api, config.read_only) {
+                exit_with_error(&msg);
+            }
+        }
         Some(("info", task_info_arg)) => {
-            let task_id: &String = task_info_arg.get_one("task_id").unwrap();
-            ui::task::print_task_info(task_id.parse().unwrap(), &api);
+            let task_id = get_task_id(task_info_arg, "task_id", &api);
+            ui::task::print_task_info(task_id, &api);
+        }
+        Some(("prj", prj_arg)) => {
+            if let Err(msg) = project_commands(prj_arg, &api) {
+                exit_with_error(&msg);
+            }
+        }
+        Some(("filter", filter_arg)) => {
+            if let Err(msg) = filter_commands(filter_arg, &api) {
+                exit_with_error(&msg);
+            }
+        }
+        Some(("mv", mv_args)) => {
+            let project: &String = mv_args.get_one("project").unwrap();
+            let project =
+                ProjectID::parse(&api, project).unwrap_or_else(|msg| exit_with_error(&msg));
+            let bucket: Option<&usize> = mv_args.get_one("bucket");
+
+            for task_id in get_task_ids(mv_args, "task_id", &api) {
+                if let Err(msg) = api.move_task(task_id, &project, bucket.copied()) {
+                    exit_with_error(&msg);
+                }
+            }
+        }
+        Some(("dup", dup_args)) => {
+            let project: Option<&String> = dup_args.get_one("project");
+            let project = project
+                .map(|x| ProjectID::parse(&api, x).unwrap_or_else(|msg| exit_with_error(&msg)));
+
+            for task_id in get_task_ids(dup_args, "task_id", &api) {
+                match api.duplicate_task(task_id, project.as_ref()) {
+                    Ok(copy) => println!("Duplicated ({task_id}) as ({})", copy.id),
+                    Err(msg) => exit_with_error(&msg),
+                }
+            }
         }
-        Some(("prj", prj_arg)) => project_commands(prj_arg, &api),
         Some(("rm", rm_args)) => {
-            let task_id: &String = rm_args.get_one("task_id").unwrap();
-            api.delete_task(task_id.parse().unwrap());
+            let tasks = select_tasks(rm_args, &api).unwrap_or_else(|msg| exit_with_error(&msg));
+
+            if !confirm_selection(rm_args, &api, &tasks, "Delete", true, |_| false) {
+                return;
+            }
+
+            for task in tasks {
+                let entry = trash::snapshot_task(&api, task.id)
+                    .and_then(|x| trash::save(trash::TrashItem::Task(x)))
+                    .unwrap_or_else(|msg| exit_with_error(&msg));
+                api.delete_task(task.id);
+                println!(
+                    "Deleted ({}), restore it with `vk trash restore {}`",
+                    task.id, entry.id
+                );
+            }
+        }
+        Some(("graph", graph_arg)) => {
+            let project: Option<&String> = graph_arg.get_one("project");
+            let project = project
+                .map(|x| ProjectID::parse(&api, x).unwrap_or_else(|msg| exit_with_error(&msg)));
+            let format: &String = graph_arg.get_one("format").unwrap();
+
+            ui::graph::print_graph(
+                &api,
+                project.as_ref(),
+                ui::graph::GraphFormat::try_parse(format).unwrap(),
+            )
+            .unwrap_or_else(|msg| exit_with_error(&msg));
+        }
+        Some(("next", next_arg)) => {
+            let project: Option<&String> = next_arg.get_one("project");
+            let project = project
+                .map(|x| ProjectID::parse(&api, x).unwrap_or_else(|msg| exit_with_error(&msg)));
+            let limit: &usize = next_arg.get_one("limit").unwrap();
+
+            ui::task::print_next_tasks(&api, project.as_ref(), *limit, arg.get_flag("identifier"))
+                .unwrap_or_else(|msg| exit_with_error(&msg));
+        }
+        Some(("tree", tree_arg)) => {
+            let task_id = get_task_id(tree_arg, "task_id", &api);
+            let depth: &usize = tree_arg.get_one("depth").unwrap();
+            ui::task::print_task_tree(task_id, *depth, &api);
+        }
+        Some(("progress", progress_arg)) => {
+            if let Some(project) = progress_arg.get_one::<String>("project") {
+                let project =
+                    ProjectID::parse(&api, project).unwrap_or_else(|msg| exit_with_error(&msg));
+                ui::project::print_progress(&api, &project)
+                    .unwrap_or_else(|msg| exit_with_error(&msg));
+                return;
+            }
+
+            let task_id = get_task_id(progress_arg, "task_id", &api);
+
+            if let Some(percent) = progress_arg.get_one::<String>("percent") {
+                let percent: f64 = percent
+                    .trim()
+                    .trim_end_matches('%')
+                    .parse()
+                    .ok()
+                    .filter(|x| (0.0..=100.0).contains(x))
+                    .unwrap_or_else(|| {
+                        exit_with_error(&format!("'{percent}' is no percentage between 0 and 100"))
+                    });
+
+                if let Err(msg) = api.set_task_progress(task_id, percent / 100.0) {
+                    exit_with_error(&msg);
+                }
+            }
+
+            ui::task::print_task_info(task_id, &api);
         }
+        Some((cmd @ ("check" | "uncheck"), check_arg)) => {
+            let task_id = get_task_id(check_arg, "task_id", &api);
+            let task = api
+                .get_task(task_id)
+                .unwrap_or_else(|()| exit_with_error(&format!("Could not get task #{task_id}")));
+
+            let mut description = task.description;
+            for item in check_arg.get_many::<usize>("item").unwrap() {
+                description = checklist::set_checked(&description, *item, cmd == "check")
+                    .unwrap_or_else(|msg| exit_with_error(&msg));
+            }
+
+            match api.set_task_description(task_id, &description) {
+                Ok(task) => ui::task::print_checklist(&task.description),
+                Err(msg) => exit_with_error(&msg),
+            }
+        }
+        Some(("checklist", checklist_arg)) => match checklist_arg.subcommand() {
+            Some(("add", add_arg)) => {
+                let task_id = get_task_id(add_arg, "task_id", &api);
+                let item: &String = add_arg.get_one("item").unwrap();
+                let task = api.get_task(task_id).unwrap_or_else(|()| {
+                    exit_with_error(&format!("Could not get task #{task_id}"))
+                });
+
+                let description = checklist::append(&task.description, item);
+                match api.set_task_description(task_id, &description) {
+                    Ok(task) => ui::task::print_checklist(&task.description),
+                    Err(msg) => exit_with_error(&msg),
+                }
+            }
+            Some(("ls", ls_arg)) => {
+                let task_id = get_task_id(ls_arg, "task_id", &api);
+                match api.get_task(task_id) {
+                    Ok(task) => ui::task::print_checklist(&task.description),
+                    Err(()) => exit_with_error(&format!("Could not get task #{task_id}")),
+                }
+            }
+            _ => {
+                exit_with_error("Use `vk checklist ls <task>` or `vk checklist add <task> <item>`")
+            }
+        },
+        Some(("trash", trash_arg)) => match trash_arg.subcommand() {
+            Some(("restore", restore_arg)) => {
+                let id: &usize = restore_arg.get_one("id").unwrap();
+                match trash::restore(&api, Some(*id)) {
+                    Ok(msg) => println!("{msg}"),
+                    Err(msg) => exit_with_error(&msg),
+                }
+            }
+            _ => ui::trash::print_trash(&trash::entries()),
+        },
+        Some(("undo", _)) => match trash::restore(&api, None) {
+            Ok(msg) => println!("{msg}"),
+            Err(msg) => exit_with_error(&msg),
+        },
         Some(("assign", assign_arg)) => {
-            let user: &String = assign_arg.get_one("user").unwrap();
-            let task_id: &String = assign_arg.get_one("task_id").unwrap();
+            let users: &String = assign_arg.get_one("user").unwrap();
             let undo = assign_arg.get_flag("undo");
 
-            if undo {
-                api.remove_assign_to_task(user, task_id.parse().unwrap());
-            } else if let Err(msg) = api.assign_to_task(user, task_id.parse().unwrap()) {
-                print_color(crossterm::style::Color::Red, &msg);
-                println!();
-            }
+            bulk_command(
+                assign_arg,
+                &api,
+                "Assign",
+                false,
+                |_| false,
+                |tasks| {
+                    for task in tasks {
+                        if undo {
+                            let assigned = task.assignees.clone().unwrap_or_default();
+                            for user in split_list(users) {
+                                let user = if user == "me" {
+                                    api.current_user()?
+                                } else {
+                                    resolve(
+                                        "Assignee",
+                                        user,
+                                        &assigned,
+                                        |x| vec![x.username.clone()],
+                                        |x| format!("{} ({})", x.username, x.name),
+                                    )?
+                                    .clone()
+                                };
+                                api.remove_assign_to_task(&user, task.id)?;
+                            }
+                        } else {
+                            for user in split_list(users) {
+                                let user = api.resolve_assignee(user, task.project_id)?;
+                                api.assign_to_task(&user, task.id)?;
+                            }
+                        }
+                    }
+                    Ok(())
+                },
+            );
         }
         Some(("comments", c_arg)) => {
-            let task_id: &String = c_arg.get_one("task_id").unwrap();
-            let comments = api.get_task_comments(task_id.parse().unwrap());
+            if let Err(msg) = api.require(Feature::Comments) {
+                exit_with_error(&msg);
+            }
+            let task_id = get_task_id(c_arg, "task_id", &api);
+            let comments = api.get_task_comments(task_id);
 
             for comment in comments {
                 ui::task::print_comment(&comment);
             }
         }
         Some(("comment", comment_arg)) => {
-            let task_id: &String = comment_arg.get_one("task_id").unwrap();
-            let comment: &String = comment_arg.get_one("comment").unwrap();
-
-            api.new_comment(task_id.parse().unwrap(), comment);
+            if let Err(msg) = comment_commands(comment_arg, &api) {
+                exit_with_error(&msg);
+            }
+        }
+        Some(("whoami", _)) => ui::user::print_whoami(&api, &config.token),
+        Some(("settings", settings_args)) => {
+            if let Err(msg) = settings_commands(settings_args, &api) {
+                exit_with_error(&msg);
+            }
+        }
+        Some(("team", team_args)) => {
+            if let Err(msg) = team_commands(team_args, &api) {
+                exit_with_error(&msg);
+            }
+        }
+        Some(("labels", label_args)) => {
+            if let Err(msg) = label_commands(label_args, &api) {
+                exit_with_error(&msg);
+            }
         }
-        Some(("labels", label_args)) => label_commands(label_args, &api),
         Some(("label", label_args)) => {
-            let label: &String = label_args.get_one("label").unwrap();
-            let task_id: &String = label_args.get_one("task_id").unwrap();
-            let undo = label_args.get_flag("undo");
-
-            if undo {
-                api.label_task_remove(label, task_id.parse().unwrap());
-            } else if let Err(msg) = api.label_task(label, task_id.parse().unwrap()) {
-                print_color(crossterm::style::Color::Red, &msg);
-                println!();
-                std::process::exit(1);
+            let mut changes = LabelChanges::parse(label_args.get_one::<String>("label"));
+            if label_args.get_flag("undo") {
+                let add = std::mem::take(&mut changes.add);
+                changes.remove.extend(add);
             }
-            ui::task::print_task_info(task_id.parse().unwrap(), &api);
+
+            // Missing labels are only created once the selection is confirmed
+            let create_missing = label_args.get_flag("create-missing");
+            let (add, missing) = find_labels(&api, &changes.add, create_missing)
+                .unwrap_or_else(|msg| exit_with_error(&msg));
+            let remove = resolve_labels(&api, &changes.remove, false)
+                .unwrap_or_else(|msg| exit_with_error(&msg));
+
+            bulk_command(
+                label_args,
+                &api,
+                "Label",
+                true,
+                |_| false,
+                |tasks| {
+                    let mut add = add.clone();
+                    add.extend(create_labels(&api, &missing));
+
+                    for task in tasks {
+                        api.change_task_labels(task, &add, &remove)?;
+                    }
+                    Ok(())
+                },
+            );
         }
         Some(("new", new_task_arg)) => {
             let title: &String = new_task_arg.get_one("title").unwrap();
-            let project: &String = new_task_arg.get_one("project").unwrap();
-            let project = ProjectID::parse(&api, project).unwrap();
+            let project = match new_task_arg.get_one::<String>("project") {
+                Some(project) => {
+                    ProjectID::parse(&api, project).unwrap_or_else(|msg| exit_with_error(&msg))
+                }
+                None => api.default_project().unwrap_or_else(|| {
+                    exit_with_error(
+                        "No project given, no default project set and no Inbox, set one with `vk settings set default_project <project>`",
+                    )
+                }),
+            };
             let description: Option<String> = new_task_arg
                 .get_one::<String>("description")
                 .map(std::borrow::ToOwned::to_owned);
@@ -223,13 +1129,30 @@ fn main() {
                     std::process::exit(1);
                 }
             });
-            let label: Option<String> = new_task_arg
-                .get_one::<String>("label")
-                .map(std::borrow::ToOwned::to_owned);
+            let labels = LabelChanges::parse(
+                new_task_arg
+                    .get_many::<String>("label")
+                    .into_iter()
+                    .flatten(),
+            );
+            if !labels.remove.is_empty() {
+                exit_with_error("Labels can not be removed from a new task");
+            }
+            let labels = resolve_labels(&api, &labels.add, new_task_arg.get_flag("create-missing"))
+                .unwrap_or_else(|msg| exit_with_error(&msg));
             let priority: Option<String> = new_task_arg
                 .get_one::<String>("priority")
                 .map(std::borrow::ToOwned::to_owned);
             let fav = new_task_arg.get_flag("favorite");
+            let assignees: Vec<_> = new_task_arg
+                .get_one::<String>("assign")
+                .map(|x| split_list(x).collect())
+                .unwrap_or_default();
+            let assignees = assignees
+                .into_iter()
+                .map(|x| api.resolve_assignee(x, project.0))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap_or_else(|msg| exit_with_error(&msg));
             // todo : add args
 
             let task = api.new_task(
@@ -238,7 +1161,7 @@ fn main() {
                 description,
                 due_date,
                 fav,
-                label,
+                &labels,
                 priority.map(|x| x.parse().unwrap()),
             );
             if let Err(msg) = task {
@@ -246,52 +1169,85 @@ fn main() {
                 println!();
                 std::process::exit(1);
             } else {
-                ui::task::print_task_info(task.unwrap().id, &api);
+                let task = task.unwrap();
+                for user in &assignees {
+                    if let Err(msg) = api.assign_to_task(user, task.id) {
+                        exit_with_error(&msg);
+                    }
+                }
+                ui::task::print_task_info(task.id, &api);
             }
         }
         Some(("done", done_args)) => {
-            let task_id: &String = done_args.get_one("task_id").unwrap();
             let done = !done_args.get_flag("undo");
-            api.done_task(task_id.parse().unwrap(), done);
-            ui::task::print_task_info(task_id.parse().unwrap(), &api);
+
+            let warn = |tasks: &[Task]| done && ui::task::print_open_blockers(tasks);
+            let tasks = bulk_command(done_args, &api, "Complete", true, warn, |tasks| {
+                if let [task] = tasks {
+                    return api.done_task(task.id, done).map(|_| ());
+                }
+
+                let ids: Vec<isize> = tasks.iter().map(|x| x.id).collect();
+                api.bulk_update_tasks(
+                    &ids,
+                    &serde_json::json!({
+                        "done": done,
+                        "done_at": if done { Some(chrono::Utc::now().to_rfc3339()) } else { None }
+                    }),
+                )
+                .map(|_| ())
+            });
+
+            if done {
+                ui::task::print_unblocked_tasks(&api, &tasks, arg.get_flag("identifier"));
+            }
         }
         Some(("fav", fav_args)) => {
-            let task_id: &String = fav_args.get_one("task_id").unwrap();
             let undo = fav_args.get_flag("undo");
 
-            api.fav_task(task_id.parse().unwrap(), !undo);
-            ui::task::print_task_info(task_id.parse().unwrap(), &api);
+            bulk_command(
+                fav_args,
+                &api,
+                "Favorite",
+                true,
+                |_| false,
+                |tasks| {
+                    for task in tasks {
+                        api.fav_task(task.id, !undo)?;
+                    }
+                    Ok(())
+                },
+            );
         }
         Some(("relation", rel_args)) => {
-            let task_id: &String = rel_args.get_one("task_id").unwrap();
+            if let Some(("ls", ls_args)) = rel_args.subcommand() {
+                let task_id = get_task_id(ls_args, "task_id", &api);
+                ui::task::print_relations(task_id, &api, arg.get_flag("identifier"));
+                return;
+            }
+
+            let task_id = get_task_id(rel_args, "task_id", &api);
             let relation: &String = rel_args.get_one("relation").unwrap();
-            let sec_task_id: &String = rel_args.get_one("second_task_id").unwrap();
+            let sec_task_id = get_task_id(rel_args, "second_task_id", &api);
             let delete = rel_args.get_flag("delete");
 
-            let relation = Relation::try_parse(relation).unwrap();
+            let relation = Relation::parse(relation).unwrap_or_else(|msg| exit_with_error(&msg));
 
             if delete {
-                api.remove_relation(
-                    task_id.parse().unwrap(),
-                    &relation,
-                    sec_task_id.parse().unwrap(),
-                );
-            } else {
-                api.add_relation(
-                    task_id.parse().unwrap(),
-                    &relation,
-                    sec_task_id.parse().unwrap(),
-                );
+                api.remove_relation(task_id, &relation, sec_task_id);
+            } else if let Err(msg) = api.relate_tasks(task_id, &relation, sec_task_id) {
+                exit_with_error(&msg);
             }
 
-            ui::task::print_task_info(task_id.parse().unwrap(), &api);
+            ui::task::print_task_info(task_id, &api);
         }
         _ => {
             let done = arg.get_flag("done");
             let fav = arg.get_flag("favorite");
             let project: Option<&String> = arg.get_one("from");
             let label: Option<&String> = arg.get_one("label");
-            ui::task::print_current_tasks(&api, done, fav, project, label);
+            let identifier = arg.get_flag("identifier");
+            ui::task::print_current_tasks(&api, done, fav, project, label, identifier);
         }
     }
 }
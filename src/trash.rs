@@ -0,0 +1,402 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::{Comment, Project, ProjectID, Relation, Task, VikunjaAPI},
+    ui::{plural, print_color, project::project_subtree},
+};
+
+/// Snapshots of deleted tasks and projects are kept here
+static TRASH_PATH: Lazy<PathBuf> = Lazy::new(|| {
+    dirs::data_dir()
+        .unwrap_or_else(|| dirs::home_dir().unwrap().join(".local").join("share"))
+        .join("vk")
+        .join("trash")
+});
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskSnapshot {
+    pub task: Task,
+    pub comments: Vec<Comment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSnapshot {
+    pub project: Project,
+    pub tasks: Vec<TaskSnapshot>,
+    pub children: Vec<ProjectSnapshot>,
+}
+
+impl ProjectSnapshot {
+    /// Amount of tasks in this project and all sub projects
+    pub fn task_count(&self) -> usize {
+        self.tasks.len() + self.children.iter().map(Self::task_count).sum::<usize>()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum TrashItem {
+    Task(TaskSnapshot),
+    Project(ProjectSnapshot),
+}
+
+/// What a failed restore already recreated, so retrying it does not duplicate anything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Restored {
+    /// IDs of the recreated projects by their old ID
+    pub projects: HashMap<isize, isize>,
+    /// IDs of the recreated tasks by their old ID
+    pub tasks: HashMap<isize, isize>,
+    /// Old IDs of the recreated comments
+    pub comments: Vec<isize>,
+    /// Recreated relations between restored tasks as old task ID, kind and old other task ID
+    pub relations: Vec<(isize, String, isize)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub id: usize,
+    pub deleted: String,
+    pub item: TrashItem,
+    #[serde(default)]
+    pub restored: Restored,
+}
+
+pub fn snapshot_task(api: &VikunjaAPI, task_id: isize) -> Result<TaskSnapshot, String> {
+    let task = api
+        .get_task(task_id)
+        .map_err(|()| format!("Could not get task #{task_id}"))?;
+
+    Ok(TaskSnapshot {
+        comments: api.get_task_comments(task_id),
+        task,
+    })
+}
+
+/// Title, amount of tasks and amount of sub projects of `project`.
+///
+/// Cheaper than a snapshot, as it does not fetch the comments of every task.
+pub fn project_summary(
+    api: &VikunjaAPI,
+    project: &ProjectID,
+) -> Result<(String, usize, usize), String> {
+    if project.0 < 0 {
+        return Err(String::from("Saved filters can not be put into the trash"));
+    }
+
+    let projects = api.get_projects(true);
+    let title = projects.iter().find(|x| x.id == project.0).map_or_else(
+        || Err(format!("Project #{} not found", project.0)),
+        |x| Ok(x.title.clone()),
+    )?;
+
+    let subtree = project_subtree(project.0, &projects);
+    let tasks = api
+        .get_all_tasks()
+        .iter()
+        .filter(|x| subtree.contains(&x.project_id))
+        .count();
+
+    Ok((title, tasks, subtree.len() - 1))
+}
+
+/// Snapshot a project with its tasks and all sub projects
+pub fn snapshot_project(api: &VikunjaAPI, project: &ProjectID) -> Result<ProjectSnapshot, String> {
+    fn build(
+        project: Project,
+        projects: &[Project],
+        tasks: &[Task],
+        api: &VikunjaAPI,
+        seen: &mut Vec<isize>,
+    ) -> Result<ProjectSnapshot, String> {
+        seen.push(project.id);
+
+        let children = projects
+            .iter()
+            .filter(|x| x.parent_project_id == project.id && !seen.contains(&x.id))
+            .cloned()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|x| build(x, projects, tasks, api, seen))
+            .collect::<Result<_, _>>()?;
+
+        Ok(ProjectSnapshot {
+            tasks: tasks
+                .iter()
+                .filter(|x| x.project_id == project.id)
+                .map(|x| snapshot_task(api, x.id))
+                .collect::<Result<_, _>>()?,
+            project,
+            children,
+        })
+    }
+
+    if project.0 < 0 {
+        return Err(String::from("Saved filters can not be put into the trash"));
+    }
+
+    let projects = api.get_projects(true);
+    let tasks = api.get_all_tasks();
+    let root = projects
+        .iter()
+        .find(|x| x.id == project.0)
+        .cloned()
+        .map_or_else(|| Err(format!("Project #{} not found", project.0)), Ok)?;
+
+    build(root, &projects, &tasks, api, &mut Vec::new())
+}
+
+/// All trash entries, oldest first
+pub fn entries() -> Vec<TrashEntry> {
+    let Ok(dir) = std::fs::read_dir(TRASH_PATH.as_path()) else {
+        return Vec::new();
+    };
+
+    let mut entries: Vec<TrashEntry> = dir
+        .filter_map(Result::ok)
+        .map(|x| x.path())
+        .filter(|x| x.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let entry = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|x| serde_json::from_str(&x).map_err(|e| e.to_string()));
+            if let Err(e) = &entry {
+                print_color(
+                    crossterm::style::Color::Yellow,
+                    &format!("Warning: skipping trash entry {}: {e}\n", path.display()),
+                );
+            }
+            entry.ok()
+        })
+        .collect();
+
+    entries.sort_by_key(|x| x.id);
+    entries
+}
+
+fn entry_path(id: usize) -> PathBuf {
+    TRASH_PATH.join(format!("{id}.json"))
+}
+
+fn write(entry: &TrashEntry) -> Result<(), String> {
+    std::fs::write(
+        entry_path(entry.id),
+        serde_json::to_string_pretty(entry).unwrap(),
+    )
+    .map_err(|e| format!("Could not write trash entry: {e}"))
+}
+
+/// The next free entry ID, never reusing IDs of restored entries
+fn next_id() -> Result<usize, String> {
+    let path = TRASH_PATH.join("next_id");
+    let stored = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|x| x.trim().parse().ok())
+        .unwrap_or(1);
+    let id = entries().last().map_or(stored, |x| stored.max(x.id + 1));
+
+    std::fs::write(&path, (id + 1).to_string())
+        .map_err(|e| format!("Could not write trash entry ID: {e}"))?;
+    Ok(id)
+}
+
+/// Save `item` into the trash before it gets deleted
+pub fn save(item: TrashItem) -> Result<TrashEntry, String> {
+    std::fs::create_dir_all(TRASH_PATH.as_path()).map_err(|e| e.to_string())?;
+
+    let entry = TrashEntry {
+        id: next_id()?,
+        deleted: chrono::Utc::now().to_rfc3339(),
+        item,
+        restored: Restored::default(),
+    };
+    write(&entry)?;
+
+    Ok(entry)
+}
+
+/// Recreate a task and its comments, skipping what `entry` already restored
+fn restore_task(
+    api: &VikunjaAPI,
+    entry: &mut TrashEntry,
+    snapshot: &TaskSnapshot,
+    project_id: isize,
+) -> Result<Task, String> {
+    let old_id = snapshot.task.id;
+    let task = match entry.restored.tasks.get(&old_id) {
+        Some(id) => api
+            .get_task(*id)
+            .map_err(|()| format!("Could not get restored task #{id}"))?,
+        None => {
+            let task = api.recreate_task(&snapshot.task, project_id)?;
+            entry.restored.tasks.insert(old_id, task.id);
+            write(entry)?;
+            task
+        }
+    };
+
+    for comment in &snapshot.comments {
+        if !entry.restored.comments.contains(&comment.id) {
+            api.new_comment(task.id, &comment.comment)?;
+            entry.restored.comments.push(comment.id);
+            write(entry)?;
+        }
+    }
+
+    Ok(task)
+}
+
+/// Recreate a project with its tasks and sub projects, skipping what `entry` already restored
+fn restore_project(
+    api: &VikunjaAPI,
+    entry: &mut TrashEntry,
+    snapshot: &ProjectSnapshot,
+    parent: Option<ProjectID>,
+) -> Result<Project, String> {
+    let prj = &snapshot.project;
+    let mut project = match entry.restored.projects.get(&prj.id) {
+        Some(id) => api.get_project(&ProjectID(*id)),
+        None => {
+            let project = api.new_project(
+                &prj.title,
+                Some(&prj.description),
+                Some(&prj.hex_color),
+                parent,
+            );
+            entry.restored.projects.insert(prj.id, project.id);
+            write(entry)?;
+            project
+        }
+    };
+
+    if project.identifier != prj.identifier {
+        project.identifier.clone_from(&prj.identifier);
+        project = api.update_project(&project)?;
+    }
+
+    for task in &snapshot.tasks {
+        restore_task(api, entry, task, project.id)?;
+    }
+
+    for child in &snapshot.children {
+        restore_project(api, entry, child, Some(ProjectID(project.id)))?;
+    }
+
+    Ok(project)
+}
+
+/// Recreate the relations between the tasks of a restored project.
+///
+/// Vikunja adds the inverse relation itself, so every pair is only added from
+/// the task with the lower old ID.
+fn restore_relations(
+    api: &VikunjaAPI,
+    entry: &mut TrashEntry,
+    snapshot: &ProjectSnapshot,
+) -> Result<(), String> {
+    for task in &snapshot.tasks {
+        for (kind, related) in task.task.related_tasks.iter().flatten() {
+            let Some(relation) = Relation::try_parse(kind) else {
+                continue;
+            };
+
+            for other in related {
+                let key = (task.task.id, kind.clone(), other.id);
+                if task.task.id > other.id || entry.restored.relations.contains(&key) {
+                    continue;
+                }
+                let (Some(task_id), Some(other_id)) = (
+                    entry.restored.tasks.get(&task.task.id),
+                    entry.restored.tasks.get(&other.id),
+                ) else {
+                    continue;
+                };
+
+                api.add_relation(*task_id, &relation, *other_id)?;
+                entry.restored.relations.push(key);
+                write(entry)?;
+            }
+        }
+    }
+
+    for child in &snapshot.children {
+        restore_relations(api, entry, child)?;
+    }
+
+    Ok(())
+}
+
+/// Archive the restored projects that were archived before deletion
+fn restore_archived(
+    api: &VikunjaAPI,
+    entry: &TrashEntry,
+    snapshot: &ProjectSnapshot,
+) -> Result<(), String> {
+    for child in &snapshot.children {
+        restore_archived(api, entry, child)?;
+    }
+
+    if snapshot.project.is_archived {
+        if let Some(id) = entry.restored.projects.get(&snapshot.project.id) {
+            api.archive_project(&ProjectID(*id), true)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Recreate the trash entry `id` or the latest one and remove it from the trash.
+///
+/// Progress is saved to the entry, so a failed restore can be retried.
+/// Returns a description of what was restored.
+pub fn restore(api: &VikunjaAPI, id: Option<usize>) -> Result<String, String> {
+    let entries = entries();
+
+    let mut entry = match id {
+        Some(id) => entries.into_iter().find(|x| x.id == id),
+        None => entries.into_iter().last(),
+    }
+    .map_or_else(|| Err(String::from("Nothing to restore")), Ok)?;
+
+    let restored = match entry.item.clone() {
+        TrashItem::Task(snapshot) => {
+            let project_id = snapshot.task.project_id;
+            if !api.get_projects(true).iter().any(|x| x.id == project_id) {
+                return Err(format!(
+                    "The project #{project_id} of '{}' does not exist anymore",
+                    snapshot.task.title
+                ));
+            }
+
+            let task = restore_task(api, &mut entry, &snapshot, project_id)?;
+            format!("Restored task '{}' as ({})", task.title, task.id)
+        }
+        TrashItem::Project(snapshot) => {
+            let parent_id = snapshot.project.parent_project_id;
+            let parent = api
+                .get_all_projects()
+                .iter()
+                .any(|x| x.id == parent_id)
+                .then_some(ProjectID(parent_id));
+
+            let project = restore_project(api, &mut entry, &snapshot, parent)?;
+            restore_relations(api, &mut entry, &snapshot)?;
+            restore_archived(api, &entry, &snapshot)?;
+
+            format!(
+                "Restored project '{}' with {} as [{}]",
+                project.title,
+                plural(snapshot.task_count(), "task"),
+                project.id
+            )
+        }
+    };
+
+    std::fs::remove_file(entry_path(entry.id)).map_err(|e| e.to_string())?;
+
+    Ok(restored)
+}
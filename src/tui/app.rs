@@ -0,0 +1,430 @@
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+use crate::{
+    api::{Feature, LabelChanges, Project, ProjectID, Task, TaskSelector, VikunjaAPI},
+    ui,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Projects,
+    Tasks,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prompt {
+    Label,
+    Comment,
+    NewTask,
+    Filter,
+}
+
+impl Prompt {
+    pub const fn repr(self) -> &'static str {
+        match self {
+            Self::Label => "Labels (a,+b,-c)",
+            Self::Comment => "Comment",
+            Self::NewTask => "New task",
+            Self::Filter => "Filter (title or project=X && done=false)",
+        }
+    }
+}
+
+/// Text typed into the prompt line
+pub struct Input {
+    pub prompt: Prompt,
+    pub text: String,
+}
+
+/// Task list filter, either a title search or a `--filter` expression
+pub enum Filter {
+    Title(String),
+    Selector(String, TaskSelector),
+}
+
+impl Filter {
+    fn matches(&self, task: &Task) -> bool {
+        match self {
+            Self::Title(search) => task.title.to_lowercase().contains(&search.to_lowercase()),
+            Self::Selector(_, selector) => selector.matches(task),
+        }
+    }
+
+    pub fn repr(&self) -> &str {
+        match self {
+            Self::Title(search) | Self::Selector(search, _) => search,
+        }
+    }
+}
+
+/// State of the TUI, changed by key events and drawn by `view::draw`
+pub struct App<'a> {
+    pub api: &'a VikunjaAPI,
+    read_only: bool,
+    /// All projects, sorted as a tree with their depth
+    pub projects: Vec<(usize, Project)>,
+    pub tasks: Vec<Task>,
+    /// Sidebar row, 0 shows all projects
+    pub project: usize,
+    /// IDs of the selected project and its sub projects, `None` for all projects
+    subtree: Option<Vec<isize>>,
+    /// Row in the visible task list
+    pub task: usize,
+    pub focus: Focus,
+    pub show_done: bool,
+    pub filter: Option<Filter>,
+    pub input: Option<Input>,
+    /// Result of the last action, `Err` is shown in red
+    pub message: Option<Result<String, String>>,
+    pub quit: bool,
+}
+
+impl<'a> App<'a> {
+    pub fn new(api: &'a VikunjaAPI, read_only: bool) -> Self {
+        let mut app = Self {
+            api,
+            read_only,
+            projects: Vec::new(),
+            tasks: Vec::new(),
+            project: 0,
+            subtree: None,
+            task: 0,
+            focus: Focus::Tasks,
+            show_done: false,
+            filter: None,
+            input: None,
+            message: None,
+            quit: false,
+        };
+        app.reload();
+        app
+    }
+
+    /// Fetch projects and tasks again
+    pub fn reload(&mut self) {
+        self.api.clear_cache();
+
+        let projects: Vec<Project> = self
+            .api
+            .get_all_projects()
+            .into_iter()
+            .filter(|x| x.id > 0)
+            .collect();
+        self.projects = project_tree(&projects);
+        self.tasks = self.api.get_all_tasks();
+
+        self.project = self.project.min(self.projects.len());
+        self.update_subtree();
+        self.clamp_task();
+    }
+
+    pub fn selected_project(&self) -> Option<&Project> {
+        self.project
+            .checked_sub(1)
+            .and_then(|x| self.projects.get(x))
+            .map(|x| &x.1)
+    }
+
+    /// Remember the projects shown for the selected sidebar row
+    fn update_subtree(&mut self) {
+        self.subtree = self.selected_project().map(|prj| {
+            let projects: Vec<Project> = self.projects.iter().map(|x| x.1.clone()).collect();
+            ui::project::project_subtree(prj.id, &projects)
+        });
+    }
+
+    /// Tasks shown for the selected project, done toggle and filter
+    pub fn visible_tasks(&self) -> Vec<&Task> {
+        self.tasks
+            .iter()
+            .filter(|x| self.show_done || !x.done)
+            .filter(|x| {
+                self.subtree
+                    .as_ref()
+                    .is_none_or(|ids| ids.contains(&x.project_id))
+            })
+            .filter(|x| self.filter.as_ref().is_none_or(|f| f.matches(x)))
+            .collect()
+    }
+
+    pub fn selected_task(&self) -> Option<&Task> {
+        self.visible_tasks().get(self.task).copied()
+    }
+
+    fn clamp_task(&mut self) {
+        self.task = self.task.min(self.visible_tasks().len().saturating_sub(1));
+    }
+
+    /// Replace a task in the list after it was changed
+    fn update_task(&mut self, task: Task) {
+        if let Some(t) = self.tasks.iter_mut().find(|x| x.id == task.id) {
+            *t = task;
+        } else {
+            self.tasks.insert(0, task);
+        }
+        self.clamp_task();
+    }
+
+    fn refetch_task(&mut self, task_id: isize) {
+        match self.api.get_task(task_id) {
+            Ok(task) => self.update_task(task),
+            Err(()) => self.message = Some(Err(format!("Could not get task #{task_id}"))),
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+
+        if self.input.is_some() {
+            self.handle_input_key(key);
+            return;
+        }
+
+        self.message = None;
+
+        match key.code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Esc if self.filter.is_some() => {
+                self.filter = None;
+                self.clamp_task();
+            }
+            KeyCode::Esc => self.quit = true,
+            KeyCode::Tab | KeyCode::Left | KeyCode::Right => {
+                self.focus = match self.focus {
+                    Focus::Projects => Focus::Tasks,
+                    Focus::Tasks => Focus::Projects,
+                };
+            }
+            KeyCode::Enter if self.focus == Focus::Projects => self.focus = Focus::Tasks,
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Char('a') => {
+                self.show_done = !self.show_done;
+                self.clamp_task();
+            }
+            KeyCode::Char('r') => {
+                self.reload();
+                self.message = Some(Ok(String::from("Reloaded")));
+            }
+            KeyCode::Char('/') => self.prompt(Prompt::Filter),
+            KeyCode::Char('n') => self.prompt(Prompt::NewTask),
+            KeyCode::Char('d') => self.toggle_done(),
+            KeyCode::Char('f') => self.toggle_favorite(),
+            KeyCode::Char('l') => self.prompt(Prompt::Label),
+            KeyCode::Char('c') => self.prompt(Prompt::Comment),
+            _ => {}
+        }
+    }
+
+    fn handle_input_key(&mut self, key: KeyEvent) {
+        let Some(input) = self.input.as_mut() else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Esc => self.input = None,
+            KeyCode::Backspace => {
+                input.text.pop();
+            }
+            KeyCode::Char(c) => input.text.push(c),
+            KeyCode::Enter => {
+                if let Some(input) = self.input.take() {
+                    let result = self.submit(input.prompt, input.text.trim());
+                    if let Err(msg) = result {
+                        self.message = Some(Err(msg));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn move_selection(&mut self, by: isize) {
+        let tasks = self.visible_tasks().len();
+        let (pos, len) = match self.focus {
+            Focus::Projects => (&mut self.project, self.projects.len() + 1),
+            Focus::Tasks => (&mut self.task, tasks),
+        };
+
+        *pos = pos.saturating_add_signed(by).min(len.saturating_sub(1));
+
+        if self.focus == Focus::Projects {
+            self.task = 0;
+            self.update_subtree();
+        }
+    }
+
+    fn check_writable(&mut self) -> bool {
+        if self.read_only {
+            self.message = Some(Err(String::from(
+                "This profile uses a link share and is read-only",
+            )));
+        }
+        !self.read_only
+    }
+
+    fn prompt(&mut self, prompt: Prompt) {
+        let needs_task = matches!(prompt, Prompt::Label | Prompt::Comment);
+        if needs_task && self.selected_task().is_none() {
+            return;
+        }
+
+        if prompt != Prompt::Filter && !self.check_writable() {
+            return;
+        }
+
+        if prompt == Prompt::Comment {
+            if let Err(msg) = self.api.require(Feature::Comments) {
+                self.message = Some(Err(msg));
+                return;
+            }
+        }
+
+        let text = match (&self.filter, prompt) {
+            (Some(filter), Prompt::Filter) => filter.repr().to_string(),
+            _ => String::new(),
+        };
+        self.input = Some(Input { prompt, text });
+    }
+
+    fn toggle_done(&mut self) {
+        let Some(task) = self.selected_task().cloned() else {
+            return;
+        };
+        if !self.check_writable() {
+            return;
+        }
+
+        match self.api.done_task(task.id, !task.done) {
+            Ok(done) => {
+                self.message = Some(Ok(format!(
+                    "{} #{}",
+                    if done.done { "Completed" } else { "Reopened" },
+                    done.id
+                )));
+                self.update_task(done);
+            }
+            Err(msg) => {
+                self.message = Some(Err(format!("Could not change task #{}: {msg}", task.id)));
+            }
+        }
+    }
+
+    fn toggle_favorite(&mut self) {
+        let Some(task) = self.selected_task().cloned() else {
+            return;
+        };
+        if !self.check_writable() {
+            return;
+        }
+
+        match self.api.fav_task(task.id, !task.is_favorite) {
+            Ok(fav) => self.update_task(fav),
+            Err(msg) => {
+                self.message = Some(Err(format!("Could not change task #{}: {msg}", task.id)));
+            }
+        }
+    }
+
+    fn submit(&mut self, prompt: Prompt, text: &str) -> Result<(), String> {
+        if text.is_empty() && prompt != Prompt::Filter {
+            return Ok(());
+        }
+
+        match prompt {
+            Prompt::Filter => {
+                self.filter = if text.is_empty() {
+                    None
+                } else if text.contains(['=', '<', '>']) {
+                    Some(Filter::Selector(
+                        text.to_string(),
+                        TaskSelector::parse(self.api, text)?,
+                    ))
+                } else {
+                    Some(Filter::Title(text.to_string()))
+                };
+                self.task = 0;
+            }
+            Prompt::NewTask => {
+                let project = match self.selected_project() {
+                    Some(prj) => ProjectID(prj.id),
+                    None => self.api.default_project().ok_or_else(|| {
+                        String::from("Select a project or set a default project first")
+                    })?,
+                };
+
+                let task = self
+                    .api
+                    .new_task(text, &project, None, None, false, &[], None)?;
+                self.message = Some(Ok(format!("Created #{}", task.id)));
+                self.update_task(task);
+            }
+            Prompt::Label => {
+                let Some(task) = self.selected_task().cloned() else {
+                    return Ok(());
+                };
+
+                let changes = LabelChanges::parse([&text.to_string()]);
+                let add = crate::resolve_labels(self.api, &changes.add, false)?;
+                let remove = crate::resolve_labels(self.api, &changes.remove, false)?;
+
+                self.api.change_task_labels(&task, &add, &remove)?;
+                self.refetch_task(task.id);
+            }
+            Prompt::Comment => {
+                let Some(task) = self.selected_task().cloned() else {
+                    return Ok(());
+                };
+
+                self.api.new_comment(task.id, &ui::markdown_to_html(text))?;
+                self.message = Some(Ok(format!("Commented on #{}", task.id)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Sort projects depth first below their parents
+fn project_tree(projects: &[Project]) -> Vec<(usize, Project)> {
+    let mut children: HashMap<isize, Vec<&Project>> = HashMap::new();
+    for prj in projects {
+        let parent = if projects.iter().any(|x| x.id == prj.parent_project_id) {
+            prj.parent_project_id
+        } else {
+            0
+        };
+        children.entry(parent).or_default().push(prj);
+    }
+
+    let mut tree = Vec::new();
+    let mut stack: Vec<(usize, &Project)> = children
+        .get(&0)
+        .into_iter()
+        .flatten()
+        .rev()
+        .map(|x| (0, *x))
+        .collect();
+
+    while let Some((depth, prj)) = stack.pop() {
+        if tree.iter().any(|(_, x): &(usize, Project)| x.id == prj.id) {
+            continue;
+        }
+        tree.push((depth, prj.clone()));
+
+        if let Some(sub) = children.get(&prj.id) {
+            stack.extend(sub.iter().rev().map(|x| (depth + 1, *x)));
+        }
+    }
+
+    tree
+}
@@ -0,0 +1,191 @@
+use std::io::stdout;
+
+use crossterm::{
+    event::Event,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand,
+};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    Terminal,
+};
+
+use crate::api::{Feature, VikunjaAPI};
+
+mod app;
+mod view;
+
+pub use app::App;
+
+/// Draw `app` and apply events from `next_event` until it quits.
+///
+/// Independent of the real terminal, so it can be driven by a `TestBackend`.
+pub fn event_loop<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    mut next_event: impl FnMut() -> std::io::Result<Event>,
+) -> std::io::Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| view::draw(frame, app))?;
+
+        if let Event::Key(key) = next_event()? {
+            app.handle_key(key);
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the full screen interface on the terminal
+pub fn run(api: &VikunjaAPI, read_only: bool) -> Result<(), String> {
+    api.require(Feature::Projects)?;
+
+    let mut app = App::new(api, read_only);
+
+    // Give the terminal back before a panic message is printed
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = stdout().execute(LeaveAlternateScreen);
+        let _ = disable_raw_mode();
+        hook(info);
+    }));
+
+    enable_raw_mode().map_err(|e| e.to_string())?;
+    stdout()
+        .execute(EnterAlternateScreen)
+        .map_err(|e| e.to_string())?;
+
+    let result = Terminal::new(CrosstermBackend::new(stdout()))
+        .and_then(|mut terminal| event_loop(&mut terminal, &mut app, crossterm::event::read));
+
+    let _ = stdout().execute(LeaveAlternateScreen);
+    let _ = disable_raw_mode();
+
+    result.map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+    };
+
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::{event_loop, view, App};
+    use crate::api::VikunjaAPI;
+
+    /// Method, path and body of a request the mock server received
+    type Requests = Arc<Mutex<Vec<(String, String, String)>>>;
+
+    /// Serve a Vikunja with one project and one open task, returns its host
+    fn mock_server() -> (String, Requests) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let host = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Requests::default();
+        let log = Arc::clone(&requests);
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or_default().to_string();
+
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let body = String::from_utf8(body).unwrap();
+
+                let task = serde_json::json!({
+                    "id": 1,
+                    "title": "Write docs",
+                    "project_id": 1,
+                    "done": body.contains("\"done\":true"),
+                });
+                let resp = match (method.as_str(), path.as_str()) {
+                    ("GET", x) if x.starts_with("/api/v1/projects") => {
+                        serde_json::json!([{ "id": 1, "title": "Inbox" }])
+                    }
+                    ("GET", "/api/v1/tasks/all?page=1") => serde_json::json!([task]),
+                    ("GET", x) if x.starts_with("/api/v1/tasks/all") => serde_json::json!([]),
+                    ("POST", "/api/v1/tasks/1") => task,
+                    _ => serde_json::json!({ "code": 404, "message": "Not found" }),
+                }
+                .to_string();
+
+                log.lock().unwrap().push((method, path, body));
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{resp}",
+                    resp.len()
+                )
+                .unwrap();
+            }
+        });
+
+        (host, requests)
+    }
+
+    fn screen(terminal: &Terminal<TestBackend>) -> String {
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|x| x.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn complete_task_and_quit() {
+        let (host, requests) = mock_server();
+        let api = VikunjaAPI::new(&host, "token");
+        let mut app = App::new(&api, false);
+        let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+
+        terminal.draw(|frame| view::draw(frame, &app)).unwrap();
+        assert!(screen(&terminal).contains("Write docs"));
+
+        let mut keys = [KeyCode::Char('d'), KeyCode::Char('q')].into_iter();
+        event_loop(&mut terminal, &mut app, || {
+            Ok(Event::Key(KeyEvent::new(
+                keys.next().unwrap(),
+                KeyModifiers::NONE,
+            )))
+        })
+        .unwrap();
+
+        assert!(app.quit);
+
+        let requests = requests.lock().unwrap();
+        let (_, _, body) = requests
+            .iter()
+            .find(|(method, path, _)| method == "POST" && path == "/api/v1/tasks/1")
+            .expect("task was not changed");
+        assert!(body.contains("\"done\":true"));
+
+        let after = screen(&terminal);
+        assert!(after.contains("Completed #1"));
+        assert!(!after.contains("Write docs"));
+    }
+}
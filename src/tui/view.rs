@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    api::Task,
+    ui::{self, Segment},
+};
+
+use super::app::{App, Focus};
+
+const HELP: &str =
+    "q quit  tab switch  d done  f fav  l label  c comment  n new  / filter  a show done  r reload";
+
+fn to_line(segments: Vec<Segment>) -> Line<'static> {
+    Line::from(
+        segments
+            .into_iter()
+            .map(|x| {
+                Span::styled(
+                    x.text,
+                    Style::default().fg(Color::from(x.fg)).bg(Color::from(x.bg)),
+                )
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn border(title: &str, focused: bool) -> Block<'_> {
+    let style = if focused {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default()
+    };
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(style)
+        .title(title)
+}
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [main, status] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.size());
+    let [sidebar, tasks, detail] = Layout::horizontal([
+        Constraint::Percentage(20),
+        Constraint::Percentage(45),
+        Constraint::Percentage(35),
+    ])
+    .areas(main);
+
+    draw_projects(frame, app, sidebar);
+    draw_tasks(frame, app, tasks);
+    draw_detail(frame, app, detail);
+    draw_status(frame, app, status);
+}
+
+fn draw_projects(frame: &mut Frame, app: &App, area: Rect) {
+    let mut items = vec![ListItem::new("All projects")];
+    items.extend(app.projects.iter().map(|(depth, prj)| {
+        let color = ui::hex_to_color(&prj.hex_color).map_or(Color::Reset, Color::from);
+        ListItem::new(Line::from(vec![
+            Span::raw("  ".repeat(*depth)),
+            Span::styled(prj.title.clone(), Style::default().fg(color)),
+        ]))
+    }));
+
+    let list = List::new(items)
+        .block(border("Projects", app.focus == Focus::Projects))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    let mut state = ListState::default().with_selected(Some(app.project));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_tasks(frame: &mut Frame, app: &App, area: Rect) {
+    let projects: Vec<_> = app.projects.iter().map(|x| x.1.clone()).collect();
+    let items: Vec<ListItem> = app
+        .visible_tasks()
+        .into_iter()
+        .map(|task| ListItem::new(to_line(ui::task::task_oneline(task, &projects, false))))
+        .collect();
+
+    let mut title = String::from("Tasks");
+    if app.show_done {
+        title.push_str(" (with done)");
+    }
+    if let Some(filter) = &app.filter {
+        title.push_str(&format!(" /{}", filter.repr()));
+    }
+
+    let list = List::new(items)
+        .block(border(&title, app.focus == Focus::Tasks))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    let mut state = ListState::default().with_selected(Some(app.task));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_detail(frame: &mut Frame, app: &App, area: Rect) {
+    let lines = app.selected_task().map_or_else(Vec::new, |task| {
+        let project = app
+            .projects
+            .iter()
+            .find(|x| x.1.id == task.project_id)
+            .map_or_else(String::new, |x| x.1.title.clone());
+
+        let tasks: HashMap<isize, Task> = if task.related("subtask").is_empty() {
+            HashMap::new()
+        } else {
+            app.tasks.iter().map(|x| (x.id, x.clone())).collect()
+        };
+
+        ui::task::task_info(
+            task,
+            &project,
+            &tasks,
+            area.width.saturating_sub(2) as usize,
+        )
+        .into_iter()
+        .map(to_line)
+        .collect()
+    });
+
+    let detail = Paragraph::new(lines)
+        .block(border("Details", false))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(detail, area);
+}
+
+fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
+    let line = if let Some(input) = &app.input {
+        Line::from(vec![
+            Span::styled(
+                format!("{}: ", input.prompt.repr()),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::raw(input.text.clone()),
+        ])
+    } else {
+        match &app.message {
+            Some(Ok(msg)) => Line::styled(msg.clone(), Style::default().fg(Color::Green)),
+            Some(Err(msg)) => Line::styled(msg.clone(), Style::default().fg(Color::Red)),
+            None => Line::styled(HELP, Style::default().fg(Color::DarkGray)),
+        }
+    };
+
+    frame.render_widget(Paragraph::new(line), area);
+
+    if let Some(input) = &app.input {
+        let x = area.x + (input.prompt.repr().len() + 2 + input.text.chars().count()) as u16;
+        frame.set_cursor(x.min(area.right().saturating_sub(1)), area.y);
+    }
+}
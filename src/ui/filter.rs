@@ -0,0 +1,18 @@
+use crossterm::style::Color;
+
+use crate::{api::VikunjaAPI, ui::print_color};
+
+pub fn list_filters(api: &VikunjaAPI) {
+    for filter in api.get_saved_filters() {
+        print_color(Color::Blue, &filter.title);
+        print_color(Color::Yellow, &format!(" [{}]", filter.id));
+        println!();
+
+        if !filter.description.is_empty() {
+            println!("  {}", filter.description);
+        }
+
+        print_color(Color::DarkGrey, &format!("  {}", filter.filters.describe()));
+        println!();
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::api::{Project, ProjectID, Relation, Task, VikunjaAPI};
+
+/// Output format of the dependency graph
+#[derive(Debug, Clone, Copy)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+    Json,
+}
+
+impl GraphFormat {
+    pub fn try_parse(val: &str) -> Option<Self> {
+        match val {
+            "dot" => Some(Self::Dot),
+            "mermaid" => Some(Self::Mermaid),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+struct Node {
+    title: String,
+    done: bool,
+    color: Option<String>,
+}
+
+/// Edges always point in the direction of the dependency
+fn canonical_edge(
+    task: isize,
+    kind: &Relation,
+    other: isize,
+) -> Option<(isize, isize, &'static str)> {
+    match kind {
+        Relation::Blocking => Some((task, other, "blocks")),
+        Relation::Blocked => Some((other, task, "blocks")),
+        Relation::Precedes => Some((task, other, "precedes")),
+        Relation::Follows => Some((other, task, "precedes")),
+        Relation::ParentTask => Some((other, task, "subtask")),
+        Relation::Subtask => Some((task, other, "subtask")),
+        _ => None,
+    }
+}
+
+struct Graph {
+    nodes: BTreeMap<isize, Node>,
+    edges: BTreeSet<(isize, isize, &'static str)>,
+}
+
+impl Graph {
+    fn build(tasks: &[Task], projects: &[Project]) -> Self {
+        let colors: HashMap<isize, String> = projects
+            .iter()
+            .filter(|x| !x.hex_color.is_empty())
+            .map(|x| (x.id, format!("#{}", x.hex_color.trim_start_matches('#'))))
+            .collect();
+
+        let mut nodes = BTreeMap::new();
+        let mut edges = BTreeSet::new();
+
+        let mut add_node = |task: &Task| {
+            nodes.entry(task.id).or_insert_with(|| Node {
+                title: task.title.clone(),
+                done: task.done,
+                color: colors.get(&task.project_id).cloned(),
+            });
+        };
+
+        for task in tasks {
+            add_node(task);
+
+            for (kind, related) in task.related_tasks.iter().flatten() {
+                let Some(kind) = Relation::try_parse(kind) else {
+                    continue;
+                };
+
+                for other in related {
+                    if let Some(edge) = canonical_edge(task.id, &kind, other.id) {
+                        add_node(other);
+                        edges.insert(edge);
+                    }
+                }
+            }
+        }
+
+        Self { nodes, edges }
+    }
+
+    fn dot(&self) -> String {
+        let escape = |x: &str| x.replace('\\', "\\\\").replace('"', "\\\"");
+
+        let mut out = String::from("digraph tasks {\n  node [shape=box, style=filled];\n");
+
+        for (id, node) in &self.nodes {
+            let mut attrs = vec![format!(
+                "label=\"{}#{id} {}\"",
+                if node.done { "✓ " } else { "" },
+                escape(&node.title)
+            )];
+            attrs.push(format!(
+                "fillcolor=\"{}\"",
+                node.color.as_deref().unwrap_or("#ffffff")
+            ));
+            if node.done {
+                attrs.push("style=\"filled,dashed\"".to_string());
+                attrs.push("fontcolor=\"#808080\"".to_string());
+            }
+            out.push_str(&format!("  t{id} [{}];\n", attrs.join(", ")));
+        }
+
+        for (from, to, kind) in &self.edges {
+            let style = if *kind == "subtask" {
+                ", style=dotted"
+            } else {
+                ""
+            };
+            out.push_str(&format!("  t{from} -> t{to} [label=\"{kind}\"{style}];\n"));
+        }
+
+        out.push('}');
+        out
+    }
+
+    fn mermaid(&self) -> String {
+        let escape = |x: &str| x.replace('"', "#quot;");
+
+        let mut out =
+            String::from("flowchart LR\n  classDef done stroke-dasharray: 5 5,color:#808080\n");
+
+        for (id, node) in &self.nodes {
+            out.push_str(&format!(
+                "  t{id}[\"{}#{id} {}\"]\n",
+                if node.done { "✓ " } else { "" },
+                escape(&node.title)
+            ));
+            if let Some(color) = &node.color {
+                out.push_str(&format!("  style t{id} fill:{color}\n"));
+            }
+            if node.done {
+                out.push_str(&format!("  class t{id} done\n"));
+            }
+        }
+
+        for (from, to, kind) in &self.edges {
+            let arrow = if *kind == "subtask" { "-.->" } else { "-->" };
+            out.push_str(&format!("  t{from} {arrow}|{kind}| t{to}\n"));
+        }
+
+        out
+    }
+
+    fn json(&self) -> String {
+        let nodes: Vec<_> = self
+            .nodes
+            .iter()
+            .map(|(id, node)| {
+                serde_json::json!({
+                    "id": id,
+                    "title": node.title,
+                    "done": node.done,
+                    "color": node.color
+                })
+            })
+            .collect();
+
+        let edges: Vec<_> = self
+            .edges
+            .iter()
+            .map(|(from, to, kind)| {
+                serde_json::json!({
+                    "from": from,
+                    "to": to,
+                    "kind": kind
+                })
+            })
+            .collect();
+
+        serde_json::to_string_pretty(&serde_json::json!({
+            "nodes": nodes,
+            "edges": edges
+        }))
+        .unwrap()
+    }
+}
+
+/// Print the dependency graph of the tasks in `project` or of all tasks
+pub fn print_graph(
+    api: &VikunjaAPI,
+    project: Option<&ProjectID>,
+    format: GraphFormat,
+) -> Result<(), String> {
+    let tasks = match project {
+        Some(project) if project.saved_filter_id().is_some() => api.get_project_tasks(project)?,
+        Some(project) => api
+            .get_all_tasks()
+            .into_iter()
+            .filter(|x| x.project_id == project.0)
+            .collect(),
+        None => api.get_all_tasks(),
+    };
+
+    let graph = Graph::build(&tasks, &api.get_all_projects());
+
+    println!(
+        "{}",
+        match format {
+            GraphFormat::Dot => graph.dot(),
+            GraphFormat::Mermaid => graph.mermaid(),
+            GraphFormat::Json => graph.json(),
+        }
+    );
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use std::io::stdout;
+use std::io::{stdout, BufRead, IsTerminal, Write};
 
 use chrono::{DateTime, Utc};
 use crossterm::{
@@ -8,8 +8,59 @@ use crossterm::{
 
 use crate::api::{Label, VikunjaAPI};
 
+pub mod filter;
+pub mod graph;
+pub mod picker;
 pub mod project;
 pub mod task;
+pub mod team;
+pub mod trash;
+pub mod user;
+
+/// Convert Markdown to the HTML Vikunja stores
+pub fn markdown_to_html(markdown: &str) -> String {
+    let parser = pulldown_cmark::Parser::new_ext(
+        markdown,
+        pulldown_cmark::Options::ENABLE_TABLES | pulldown_cmark::Options::ENABLE_STRIKETHROUGH,
+    );
+
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, parser);
+    html.trim_end().to_string()
+}
+
+/// Let the user write a text in `$VISUAL` or `$EDITOR`, starting with `initial`
+pub fn edit_in_editor(initial: &str) -> Result<String, String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    // Created exclusively and only readable by the user, removed when dropped
+    let mut file = tempfile::Builder::new()
+        .prefix("vk-")
+        .suffix(".md")
+        .tempfile()
+        .map_err(|e| e.to_string())?;
+    file.write_all(initial.as_bytes())
+        .and_then(|()| file.flush())
+        .map_err(|e| e.to_string())?;
+
+    let mut cmd = editor.split_whitespace();
+    let status = std::process::Command::new(cmd.next().unwrap_or("vi"))
+        .args(cmd)
+        .arg(file.path())
+        .status()
+        .map_err(|e| format!("Could not start editor '{editor}': {e}"));
+
+    // Read by path, editors may replace the file instead of writing to it
+    let text = std::fs::read_to_string(file.path()).map_err(|e| e.to_string());
+
+    if !status?.success() {
+        return Err(format!("Editor '{editor}' failed"));
+    }
+
+    text
+}
 
 fn format_html_to_terminal(html: &str) -> String {
     let width = crossterm::terminal::size().unwrap().0 as usize;
@@ -31,6 +82,49 @@ pub fn print_color_bg(color: Color, txt: &str) {
     stdout().execute(SetBackgroundColor(Color::Reset)).unwrap();
 }
 
+/// Colored piece of text, printed to the terminal or drawn by the TUI
+pub struct Segment {
+    pub text: String,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl Segment {
+    pub fn new(fg: Color, text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            fg,
+            bg: Color::Reset,
+        }
+    }
+
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self::new(Color::Reset, text)
+    }
+
+    pub fn bg(bg: Color, text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            fg: Color::Reset,
+            bg,
+        }
+    }
+}
+
+/// Print lines of segments
+pub fn print_lines(lines: &[Vec<Segment>]) {
+    for line in lines {
+        for segment in line {
+            if segment.bg == Color::Reset {
+                print_color(segment.fg, &segment.text);
+            } else {
+                print_color_bg(segment.bg, &segment.text);
+            }
+        }
+        println!();
+    }
+}
+
 /// Convert a HEX Color String into a `Color` struct
 pub fn hex_to_color(hex: &str) -> Result<Color, String> {
     let hex = hex.trim_start_matches('#');
@@ -84,6 +178,56 @@ pub fn time_relative(event: DateTime<Utc>) -> String {
     }
 }
 
+/// Render `progress` between `0.0` and `1.0` as bar of `width` characters
+pub fn progress_bar(progress: f64, width: usize) -> String {
+    let progress = progress.clamp(0.0, 1.0);
+    let filled = (progress * width as f64).round() as usize;
+
+    format!(
+        "[{}{}] {:.0}%",
+        "█".repeat(filled),
+        "░".repeat(width - filled),
+        progress * 100.0
+    )
+}
+
+/// `count` followed by `word`, with an `s` unless there is exactly one
+pub fn plural(count: usize, word: &str) -> String {
+    if count == 1 {
+        format!("{count} {word}")
+    } else {
+        format!("{count} {word}s")
+    }
+}
+
+/// Ask the user a yes/no question, defaulting to no
+///
+/// The answer is read from the terminal even if stdin was used for input, like
+/// task references piped into a bulk command.
+pub fn confirm(question: &str) -> bool {
+    let mut answer = String::new();
+
+    if std::io::stdin().is_terminal() {
+        print!("{question} [y/N] ");
+        stdout().flush().unwrap();
+        let _ = std::io::stdin().read_line(&mut answer);
+    } else if let Ok(tty) = std::fs::File::open("/dev/tty") {
+        print!("{question} [y/N] ");
+        stdout().flush().unwrap();
+        let _ = std::io::BufReader::new(tty).read_line(&mut answer);
+    } else {
+        print_color(Color::Red, "stdin is not a terminal, pass --yes");
+        println!();
+        std::process::exit(1);
+    }
+
+    let yes = matches!(answer.trim().to_lowercase().as_str(), "y" | "yes");
+    if !yes {
+        println!("Aborted");
+    }
+    yes
+}
+
 fn is_in_past(dt: DateTime<Utc>) -> bool {
     dt < Utc::now()
 }
@@ -93,11 +237,49 @@ fn print_label(label: &Label) {
     print_color_bg(color, label.title.trim());
 }
 
+/// Print all labels with the amount of open and total tasks carrying them
 pub fn print_all_labels(api: &VikunjaAPI) {
     let labels = api.get_all_labels();
+    let tasks = api.get_all_tasks();
 
     for label in labels {
+        let (open, total) = tasks
+            .iter()
+            .filter(|x| x.labels.iter().flatten().any(|l| l.id == label.id))
+            .fold((0, 0), |(open, total), task| {
+                (open + usize::from(!task.done), total + 1)
+            });
+
         print_label(&label);
-        print!("\n\n");
+        print_color(Color::DarkGrey, &format!(" {open} open, {total} tasks"));
+        println!();
     }
 }
+
+/// A color derived from `title`, so the same title always gets the same color
+pub fn auto_color(title: &str) -> String {
+    // FNV-1a, which unlike the std hasher is stable across releases
+    let hash = title.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    });
+
+    // Spread the hue and keep saturation and lightness readable
+    let hue = (hash % 360) as f64;
+    let (s, l) = (0.6, 0.5);
+
+    let c = (1.0 - (2.0 * l - 1.0_f64).abs()) * s;
+    let x = c * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let m = l - c / 2.0;
+
+    let (r, g, b) = match hue as u32 / 60 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+
+    let channel = |v: f64| ((v + m) * 255.0).round() as u8;
+    format!("{:02x}{:02x}{:02x}", channel(r), channel(g), channel(b))
+}
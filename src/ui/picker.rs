@@ -0,0 +1,345 @@
+use std::io::{stderr, IsTerminal, Write};
+
+use crossterm::{
+    cursor::{MoveToColumn, MoveUp},
+    event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal::{Clear, ClearType},
+    QueueableCommand,
+};
+
+use crate::{
+    api::{project_paths, Label, Project, ProjectID, Task, VikunjaAPI},
+    ui::{hex_to_color, task::task_oneline, Segment},
+};
+
+/// Rows shown below the query line
+const HEIGHT: usize = 10;
+
+/// Whether the user can be asked to pick something
+fn interactive() -> bool {
+    std::io::stdin().is_terminal() && stderr().is_terminal()
+}
+
+/// Score how well `query` matches `text` as a subsequence, `None` if it does not.
+///
+/// Consecutive characters and matches at word starts score higher.
+fn fuzzy_score(query: &str, text: &str) -> Option<isize> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last: Option<usize> = None;
+
+    for q in query.to_lowercase().chars().filter(|x| !x.is_whitespace()) {
+        let found = pos + text[pos..].iter().position(|x| *x == q)?;
+
+        score += 1;
+        if last.is_some_and(|x| x + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= isize::try_from(found - pos).unwrap_or(isize::MAX).min(10);
+
+        last = Some(found);
+        pos = found + 1;
+    }
+
+    Some(score)
+}
+
+struct Picker<'a> {
+    items: &'a [Vec<Segment>],
+    texts: Vec<String>,
+    multi: bool,
+    query: String,
+    /// Indices of the items matching the query, best first
+    matches: Vec<usize>,
+    cursor: usize,
+    offset: usize,
+    marked: Vec<usize>,
+}
+
+impl<'a> Picker<'a> {
+    fn new(items: &'a [Vec<Segment>], multi: bool) -> Self {
+        let mut picker = Self {
+            items,
+            texts: items
+                .iter()
+                .map(|x| x.iter().map(|x| x.text.as_str()).collect())
+                .collect(),
+            multi,
+            query: String::new(),
+            matches: Vec::new(),
+            cursor: 0,
+            offset: 0,
+            marked: Vec::new(),
+        };
+        picker.update_matches();
+        picker
+    }
+
+    fn update_matches(&mut self) {
+        let mut scored: Vec<(isize, usize)> = self
+            .texts
+            .iter()
+            .enumerate()
+            .filter_map(|(i, text)| fuzzy_score(&self.query, text).map(|x| (x, i)))
+            .collect();
+        scored.sort_by_key(|(score, i)| (-score, *i));
+
+        self.matches = scored.into_iter().map(|x| x.1).collect();
+        self.cursor = 0;
+        self.offset = 0;
+    }
+
+    fn move_cursor(&mut self, by: isize) {
+        self.cursor = self
+            .cursor
+            .saturating_add_signed(by)
+            .min(self.matches.len().saturating_sub(1));
+
+        if self.cursor < self.offset {
+            self.offset = self.cursor;
+        } else if self.cursor >= self.offset + HEIGHT {
+            self.offset = self.cursor + 1 - HEIGHT;
+        }
+    }
+
+    fn draw(&self, out: &mut impl Write, prompt: &str) -> std::io::Result<()> {
+        let width = crossterm::terminal::size()
+            .ok()
+            .filter(|x| x.0 > 0)
+            .map_or(80, |x| x.0 as usize);
+
+        out.queue(MoveToColumn(0))?
+            .queue(Clear(ClearType::FromCursorDown))?
+            .queue(SetForegroundColor(Color::Yellow))?
+            .queue(Print(format!("{prompt}> ")))?
+            .queue(ResetColor)?
+            .queue(Print(&self.query))?;
+
+        let counter = if self.multi && !self.marked.is_empty() {
+            format!(
+                "  {}/{} ({} marked)",
+                self.matches.len(),
+                self.items.len(),
+                self.marked.len()
+            )
+        } else {
+            format!("  {}/{}", self.matches.len(), self.items.len())
+        };
+        out.queue(SetForegroundColor(Color::DarkGrey))?
+            .queue(Print(counter))?
+            .queue(ResetColor)?;
+
+        let rows: Vec<usize> = self
+            .matches
+            .iter()
+            .skip(self.offset)
+            .take(HEIGHT)
+            .copied()
+            .collect();
+
+        for (row, item) in rows.iter().enumerate() {
+            let current = self.offset + row == self.cursor;
+            let mut prefix = String::from(if current { "> " } else { "  " });
+            if self.multi {
+                prefix.push_str(if self.marked.contains(item) {
+                    "[x] "
+                } else {
+                    "[ ] "
+                });
+            }
+
+            out.queue(Print("\r\n"))?
+                .queue(SetForegroundColor(Color::Yellow))?
+                .queue(Print(&prefix))?
+                .queue(ResetColor)?;
+
+            // Keep every item on one line, so redrawing can move back up
+            let mut left = width.saturating_sub(prefix.chars().count() + 2);
+            for segment in &self.items[*item] {
+                if left == 0 {
+                    break;
+                }
+                let text: String = segment.text.chars().take(left).collect();
+                left -= text.chars().count();
+
+                out.queue(SetForegroundColor(segment.fg))?
+                    .queue(SetBackgroundColor(segment.bg))?
+                    .queue(Print(text))?
+                    .queue(ResetColor)?;
+            }
+        }
+
+        if !rows.is_empty() {
+            out.queue(MoveUp(u16::try_from(rows.len()).unwrap_or(u16::MAX)))?;
+        }
+        let column = prompt.chars().count() + 2 + self.query.chars().count();
+        out.queue(MoveToColumn(u16::try_from(column).unwrap_or(u16::MAX)))?;
+
+        out.flush()
+    }
+
+    /// Apply a key, returns the picked items once the selection is done
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Result<Vec<usize>, String>> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Esc => return Some(Err(String::from("Nothing selected"))),
+            KeyCode::Char('c') if ctrl => return Some(Err(String::from("Nothing selected"))),
+            KeyCode::Enter => {
+                if !self.marked.is_empty() {
+                    return Some(Ok(self.marked.clone()));
+                }
+                return self.matches.get(self.cursor).map(|x| Ok(vec![*x]));
+            }
+            KeyCode::Up => self.move_cursor(-1),
+            KeyCode::Char('p' | 'k') if ctrl => self.move_cursor(-1),
+            KeyCode::Down => self.move_cursor(1),
+            KeyCode::Char('n' | 'j') if ctrl => self.move_cursor(1),
+            KeyCode::Tab if self.multi => {
+                if let Some(item) = self.matches.get(self.cursor).copied() {
+                    if let Some(pos) = self.marked.iter().position(|x| *x == item) {
+                        self.marked.remove(pos);
+                    } else {
+                        self.marked.push(item);
+                    }
+                    self.move_cursor(1);
+                }
+            }
+            KeyCode::Backspace if !self.query.is_empty() => {
+                self.query.pop();
+                self.update_matches();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.update_matches();
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    fn run(&mut self, out: &mut impl Write, prompt: &str) -> Result<Vec<usize>, String> {
+        loop {
+            self.draw(out, prompt).map_err(|e| e.to_string())?;
+
+            if let Event::Key(key) = crossterm::event::read().map_err(|e| e.to_string())? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                if let Some(result) = self.handle_key(key) {
+                    return result;
+                }
+            }
+        }
+    }
+}
+
+/// Let the user fuzzy search `items` below the cursor and pick one of them.
+///
+/// With `multi` several items can be marked with tab. Returns the indices of the
+/// picked items.
+pub fn pick(prompt: &str, items: &[Vec<Segment>], multi: bool) -> Result<Vec<usize>, String> {
+    if items.is_empty() {
+        return Err(String::from("Nothing to pick from"));
+    }
+
+    let mut out = stderr();
+    let mut picker = Picker::new(items, multi);
+
+    crossterm::terminal::enable_raw_mode().map_err(|e| e.to_string())?;
+    let result = picker.run(&mut out, prompt);
+    let _ = crossterm::terminal::disable_raw_mode();
+
+    let _ = out
+        .queue(MoveToColumn(0))
+        .and_then(|x| x.queue(Clear(ClearType::FromCursorDown)))
+        .and_then(Write::flush);
+
+    result
+}
+
+/// Pick open tasks, several of them with `multi`
+pub fn pick_tasks(api: &VikunjaAPI, multi: bool) -> Result<Vec<Task>, String> {
+    if !interactive() {
+        return Err(String::from(if multi {
+            "No tasks given"
+        } else {
+            "No task given"
+        }));
+    }
+
+    let tasks: Vec<Task> = api
+        .get_all_tasks()
+        .into_iter()
+        .filter(|x| !x.done)
+        .collect();
+    let projects = api.get_all_projects();
+    let items: Vec<_> = tasks
+        .iter()
+        .map(|x| task_oneline(x, &projects, false))
+        .collect();
+
+    let picked = pick(if multi { "Tasks" } else { "Task" }, &items, multi)?;
+    Ok(picked.into_iter().map(|x| tasks[x].clone()).collect())
+}
+
+/// Pick a project by its path among the projects `filter` accepts.
+///
+/// Archived projects are only offered with `archived`.
+pub fn pick_project(
+    api: &VikunjaAPI,
+    archived: bool,
+    filter: impl Fn(&Project) -> bool,
+) -> Result<ProjectID, String> {
+    if !interactive() {
+        return Err(String::from("No project given"));
+    }
+
+    let mut projects = api.get_projects(archived);
+    let paths = project_paths(&projects);
+    projects.retain(|x| x.id > 0 && filter(x));
+    projects.sort_by(|a, b| paths[&a.id].cmp(&paths[&b.id]));
+
+    let items: Vec<_> = projects
+        .iter()
+        .map(|x| {
+            vec![
+                Segment::new(
+                    hex_to_color(&x.hex_color).unwrap_or(Color::Reset),
+                    &paths[&x.id],
+                ),
+                Segment::new(Color::DarkGrey, format!(" [{}]", x.id)),
+            ]
+        })
+        .collect();
+
+    let picked = pick("Project", &items, false)?;
+    Ok(ProjectID(projects[picked[0]].id))
+}
+
+/// Pick one of your labels
+pub fn pick_label(api: &VikunjaAPI) -> Result<Label, String> {
+    if !interactive() {
+        return Err(String::from("No label given"));
+    }
+
+    let labels = api.get_all_labels();
+    let items: Vec<_> = labels
+        .iter()
+        .map(|x| {
+            vec![Segment::bg(
+                hex_to_color(&x.hex_color).unwrap_or(Color::Reset),
+                x.title.trim(),
+            )]
+        })
+        .collect();
+
+    let picked = pick("Label", &items, false)?;
+    Ok(labels[picked[0]].clone())
+}
@@ -1,43 +1,264 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crossterm::style::Color;
 
 use crate::{
-    api::{Project, VikunjaAPI},
-    ui::{hex_to_color, print_color},
+    api::{progress, Project, ProjectID, VikunjaAPI},
+    ui::{hex_to_color, is_in_past, parse_datetime, print_color, progress_bar},
 };
 
-pub fn list_projects(api: &VikunjaAPI) {
-    let projects = api.get_all_projects();
+/// Open and overdue task count of a project
+#[derive(Default)]
+struct TaskCount {
+    open: usize,
+    overdue: usize,
+}
+
+struct ProjectTree<'a> {
+    children: HashMap<isize, Vec<&'a Project>>,
+    counts: Option<HashMap<isize, TaskCount>>,
+    depth: Option<usize>,
+}
+
+impl<'a> ProjectTree<'a> {
+    fn new(
+        projects: &'a [Project],
+        counts: Option<HashMap<isize, TaskCount>>,
+        depth: Option<usize>,
+    ) -> Self {
+        let mut children: HashMap<isize, Vec<&Project>> = HashMap::new();
 
-    let mut project_map: HashMap<isize, Vec<Project>> = HashMap::new();
+        for prj in projects {
+            children.entry(prj.parent_project_id).or_default().push(prj);
+        }
+
+        Self {
+            children,
+            counts,
+            depth,
+        }
+    }
 
-    for prj in projects {
-        project_map
-            .entry(prj.parent_project_id)
-            .or_default()
-            .push(prj);
+    /// Collect the IDs of all projects reachable from `roots`
+    fn reachable(&self, roots: &[&Project], seen: &mut HashSet<isize>) {
+        for prj in roots {
+            if seen.insert(prj.id) {
+                if let Some(children) = self.children.get(&prj.id) {
+                    self.reachable(children, seen);
+                }
+            }
+        }
     }
 
-    for prj in project_map.get(&0).unwrap() {
+    fn print_project(&self, prj: &Project) {
         let color = if prj.hex_color.is_empty() {
             Color::Reset
         } else {
-            hex_to_color(&prj.hex_color).unwrap()
+            hex_to_color(&prj.hex_color).unwrap_or(Color::Reset)
         };
         print_color(color, &prj.title);
-        println!(" [{}]", prj.id);
-
-        if let Some(sub_projects) = project_map.get(&(prj.id)) {
-            for sub_prj in sub_projects {
-                let color = if sub_prj.hex_color.is_empty() {
-                    Color::Reset
-                } else {
-                    hex_to_color(&sub_prj.hex_color).unwrap()
-                };
-                print_color(color, &format!("  - {}", sub_prj.title));
-                println!(" [{}]", sub_prj.id);
+        print!(" [{}]", prj.id);
+
+        if prj.is_archived {
+            print_color(Color::DarkGrey, " (archived)");
+        }
+
+        if let Some(counts) = &self.counts {
+            let count = counts.get(&prj.id);
+            print_color(
+                Color::DarkGrey,
+                &format!(" {} open", count.map_or(0, |x| x.open)),
+            );
+            if let Some(overdue) = count.map(|x| x.overdue).filter(|x| *x > 0) {
+                print_color(Color::Red, &format!(", {overdue} overdue"));
             }
         }
+
+        println!();
+    }
+
+    fn print_children(
+        &self,
+        parent: isize,
+        prefix: &str,
+        level: usize,
+        visited: &mut HashSet<isize>,
+    ) {
+        if self.depth.is_some_and(|depth| level >= depth) {
+            return;
+        }
+
+        let Some(children) = self.children.get(&parent) else {
+            return;
+        };
+
+        self.print_nodes(children, prefix, level, visited);
+    }
+
+    fn print_nodes(
+        &self,
+        nodes: &[&Project],
+        prefix: &str,
+        level: usize,
+        visited: &mut HashSet<isize>,
+    ) {
+        // Skip visited nodes first, so the last one printed gets the closing guide
+        let nodes: Vec<&Project> = nodes
+            .iter()
+            .filter(|x| visited.insert(x.id))
+            .copied()
+            .collect();
+
+        for (i, prj) in nodes.iter().enumerate() {
+            let last = i == nodes.len() - 1;
+            print!("{prefix}{}", if last { "└── " } else { "├── " });
+            self.print_project(prj);
+
+            let prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+            self.print_children(prj.id, &prefix, level + 1, visited);
+        }
+    }
+}
+
+/// Count open and overdue tasks per project
+fn task_counts(api: &VikunjaAPI) -> HashMap<isize, TaskCount> {
+    let mut counts: HashMap<isize, TaskCount> = HashMap::new();
+
+    for task in api.get_all_tasks().into_iter().filter(|x| !x.done) {
+        let count = counts.entry(task.project_id).or_default();
+        count.open += 1;
+        if parse_datetime(&task.due_date).is_some_and(is_in_past) {
+            count.overdue += 1;
+        }
+    }
+
+    counts
+}
+
+/// IDs of archived projects and all projects below them
+fn archived_projects(projects: &[Project]) -> HashSet<isize> {
+    let mut archived: HashSet<isize> = projects
+        .iter()
+        .filter(|x| x.is_archived)
+        .map(|x| x.id)
+        .collect();
+
+    loop {
+        let below: Vec<isize> = projects
+            .iter()
+            .filter(|x| !archived.contains(&x.id) && archived.contains(&x.parent_project_id))
+            .map(|x| x.id)
+            .collect();
+
+        if below.is_empty() {
+            return archived;
+        }
+
+        archived.extend(below);
+    }
+}
+
+/// Print the project tree.
+///
+/// `depth` limits how many levels below the top-level projects are shown.
+/// Projects whose parent is not visible are listed under an "orphans" node.
+pub fn list_projects(api: &VikunjaAPI, depth: Option<usize>, counts: bool, archived: bool) {
+    let (saved_filters, mut projects): (Vec<_>, Vec<_>) = api
+        .get_projects(archived)
+        .into_iter()
+        .partition(|x| ProjectID(x.id).saved_filter_id().is_some());
+
+    if !archived {
+        let hidden = archived_projects(&projects);
+        projects.retain(|x| !hidden.contains(&x.id));
+    }
+
+    let tree = ProjectTree::new(&projects, counts.then(|| task_counts(api)), depth);
+
+    let roots: Vec<&Project> = projects
+        .iter()
+        .filter(|x| x.parent_project_id == 0)
+        .collect();
+
+    let mut reachable = HashSet::new();
+    tree.reachable(&roots, &mut reachable);
+
+    let visible: HashSet<isize> = projects.iter().map(|x| x.id).collect();
+    let mut orphans: Vec<&Project> = projects
+        .iter()
+        .filter(|x| !reachable.contains(&x.id) && !visible.contains(&x.parent_project_id))
+        .collect();
+    tree.reachable(&orphans, &mut reachable);
+
+    // Projects in a parent cycle are not reachable from anywhere
+    orphans.extend(projects.iter().filter(|x| !reachable.contains(&x.id)));
+
+    let mut visited = HashSet::new();
+
+    for prj in roots {
+        visited.insert(prj.id);
+        tree.print_project(prj);
+        tree.print_children(prj.id, "", 0, &mut visited);
+    }
+
+    if !orphans.is_empty() {
+        print_color(Color::DarkGrey, "orphans");
+        println!();
+        tree.print_nodes(&orphans, "", 0, &mut visited);
     }
+
+    if !saved_filters.is_empty() {
+        println!("\nSaved Filters:");
+        for filter in saved_filters {
+            print_color(Color::Blue, &filter.title);
+            println!(" [{}]", filter.id);
+        }
+    }
+}
+
+/// IDs of `project` and all projects below it
+pub fn project_subtree(project: isize, projects: &[Project]) -> Vec<isize> {
+    let mut ids = vec![project];
+    let mut i = 0;
+
+    while i < ids.len() {
+        let parent = ids[i];
+        ids.extend(
+            projects
+                .iter()
+                .filter(|x| x.parent_project_id == parent && !ids.contains(&x.id))
+                .map(|x| x.id)
+                .collect::<Vec<_>>(),
+        );
+        i += 1;
+    }
+
+    ids
+}
+
+/// Print the progress of a project and its direct sub projects
+pub fn print_progress(api: &VikunjaAPI, project: &ProjectID) -> Result<(), String> {
+    let projects = api.get_all_projects();
+    let tasks: HashMap<isize, _> = api.get_all_tasks().into_iter().map(|x| (x.id, x)).collect();
+
+    let print_line = |prj: &Project, indent: &str| {
+        let color = hex_to_color(&prj.hex_color).unwrap_or(Color::Reset);
+        print_color(color, &format!("{indent}{}", prj.title));
+        let subtree = project_subtree(prj.id, &projects);
+        match progress::project_progress(&subtree, &tasks) {
+            Some(progress) => println!(" {}", progress_bar(progress, 20)),
+            None => print_color(Color::DarkGrey, " no tasks\n"),
+        }
+    };
+
+    let Some(root) = projects.iter().find(|x| x.id == project.0) else {
+        return Err(format!("Project #{} not found", project.0));
+    };
+
+    print_line(root, "");
+    for child in projects.iter().filter(|x| x.parent_project_id == root.id) {
+        print_line(child, "  ");
+    }
+
+    Ok(())
 }
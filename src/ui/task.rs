@@ -1,40 +1,76 @@
+use std::collections::HashMap;
+
 use crate::{
-    api::{Comment, Project, ProjectID, Relation, Task, VikunjaAPI},
+    api::{checklist, progress, Comment, Project, ProjectID, Relation, Task, TaskRef, VikunjaAPI},
     ui::{
         format_html_to_terminal, hex_to_color, is_in_past, parse_datetime, print_color,
-        print_label, time_relative,
+        print_label, print_lines, progress_bar, time_relative, Segment,
     },
 };
 
 // todo : move to grid view
-fn print_task_oneline(task: &Task, projects: &[Project]) {
-    print_color(crossterm::style::Color::Yellow, &format!("({}) ", task.id));
+pub fn print_task_oneline(task: &Task, projects: &[Project], identifier: bool) {
+    print_lines(&[task_oneline(task, projects, identifier)]);
+}
+
+/// Single line summary of a task as used by task listings
+pub fn task_oneline(task: &Task, projects: &[Project], identifier: bool) -> Vec<Segment> {
+    let project = projects.iter().find(|x| x.id == task.project_id);
+    let mut line = Vec::new();
+
+    let reference = if identifier {
+        TaskRef::display(task, project)
+    } else {
+        task.id.to_string()
+    };
+    line.push(Segment::new(
+        crossterm::style::Color::Yellow,
+        format!("({reference}) "),
+    ));
 
     if task.is_favorite {
-        print_color(crossterm::style::Color::Yellow, "⭐ ");
+        line.push(Segment::new(crossterm::style::Color::Yellow, "⭐ "));
     }
 
-    print_color(crossterm::style::Color::Blue, &task.title);
+    line.push(Segment::new(crossterm::style::Color::Blue, &task.title));
 
-    let project = projects.iter().find(|x| x.id == task.project_id).unwrap();
-    print_color(
-        hex_to_color(&project.hex_color).unwrap_or(crossterm::style::Color::Reset),
-        &format!(" [{}]", project.title),
-    );
+    if let Some(project) = project {
+        line.push(Segment::new(
+            hex_to_color(&project.hex_color).unwrap_or(crossterm::style::Color::Reset),
+            format!(" [{}]", project.title),
+        ));
+    }
 
     if task.done {
-        print_color(crossterm::style::Color::Green, " [✓]");
+        line.push(Segment::new(crossterm::style::Color::Green, " [✓]"));
+    }
+
+    if let Some((done, total)) = checklist::progress(&task.description) {
+        line.push(Segment::new(
+            crossterm::style::Color::DarkGrey,
+            format!(" [{done}/{total}]"),
+        ));
+    }
+
+    if !task.done && task.percent_done > 0.0 {
+        line.push(Segment::new(
+            crossterm::style::Color::DarkGrey,
+            format!(" {}", progress_bar(task.percent_done, 10)),
+        ));
     }
 
     if let Some(labels) = &task.labels {
-        print!(" ");
+        line.push(Segment::plain(" "));
         for label in labels {
-            print_label(label);
-            print!(" ");
+            line.push(Segment::bg(
+                hex_to_color(&label.hex_color).unwrap_or(crossterm::style::Color::Reset),
+                label.title.trim(),
+            ));
+            line.push(Segment::plain(" "));
         }
     }
 
-    println!();
+    line
 }
 
 pub fn print_current_tasks(
@@ -43,11 +79,34 @@ pub fn print_current_tasks(
     fav: bool,
     project: Option<&String>,
     label: Option<&String>,
+    identifier: bool,
 ) {
-    let current_tasks = if project.is_some() || label.is_some() {
-        api.get_all_tasks()
-    } else {
-        api.get_latest_tasks()
+    let project = project.map(|x| {
+        ProjectID::parse(api, x).unwrap_or_else(|msg| {
+            print_color(crossterm::style::Color::Red, &msg);
+            println!();
+            std::process::exit(1);
+        })
+    });
+
+    let label = label.map(|x| {
+        api.resolve_label(x).unwrap_or_else(|msg| {
+            print_color(crossterm::style::Color::Red, &msg);
+            println!();
+            std::process::exit(1);
+        })
+    });
+
+    let current_tasks = match &project {
+        Some(p_id) if p_id.saved_filter_id().is_some() => {
+            api.get_project_tasks(p_id).unwrap_or_else(|msg| {
+                print_color(crossterm::style::Color::Red, &msg);
+                println!();
+                std::process::exit(1);
+            })
+        }
+        _ if project.is_some() || label.is_some() => api.get_all_tasks(),
+        _ => api.get_latest_tasks(),
     };
 
     let mut selection: Vec<_> = if done {
@@ -62,8 +121,7 @@ pub fn print_current_tasks(
         selection
     };
 
-    if let Some(project) = project {
-        let p_id = ProjectID::parse(api, project).unwrap();
+    if let Some(p_id) = project.filter(|x| x.saved_filter_id().is_none()) {
         selection.retain(|x| x.project_id == p_id.0);
     }
 
@@ -71,7 +129,7 @@ pub fn print_current_tasks(
         selection.retain(|x| {
             if let Some(labels) = &x.labels {
                 for label in labels {
-                    if label.title.trim() == *label_match {
+                    if label.id == label_match.id {
                         return true;
                     }
                 }
@@ -83,7 +141,7 @@ pub fn print_current_tasks(
     let projects = api.get_all_projects();
 
     for task in selection {
-        print_task_oneline(&task, &projects);
+        print_task_oneline(&task, &projects, identifier);
     }
 }
 
@@ -97,106 +155,556 @@ pub fn print_task_info(task_id: isize, api: &VikunjaAPI) {
         std::process::exit(1);
     });
 
+    // All tasks are only needed for the progress of subtasks
+    let tasks: HashMap<isize, Task> = if task.related("subtask").is_empty() {
+        HashMap::new()
+    } else {
+        api.get_all_tasks().into_iter().map(|x| (x.id, x)).collect()
+    };
+
+    let width = crossterm::terminal::size().map_or(80, |x| x.0 as usize);
+    print_lines(&task_info(
+        &task,
+        &api.get_project_name_from_id(task.project_id)
+            .unwrap_or_else(|_| format!("#{}", task.project_id)),
+        &tasks,
+        width,
+    ));
+}
+
+/// Detail view of a task wrapped to `width`.
+///
+/// `tasks` are looked up for the progress of subtasks, like `vk progress` does.
+pub fn task_info(
+    task: &Task,
+    project: &str,
+    tasks: &HashMap<isize, Task>,
+    width: usize,
+) -> Vec<Vec<Segment>> {
+    let mut lines = Vec::new();
+    let mut title = Vec::new();
+
     if task.done {
-        print_color(
+        title.push(Segment::new(
             crossterm::style::Color::Green,
-            &format!(
+            format!(
                 "{} ✓ ",
                 parse_datetime(&task.done_at).map_or_else(String::new, time_relative)
             ),
-        );
+        ));
     }
 
     if task.is_favorite {
-        print!("⭐ ");
+        title.push(Segment::plain("⭐ "));
     }
 
-    print_color(crossterm::style::Color::Blue, &task.title);
-    print_color(crossterm::style::Color::Yellow, &format!(" ({})", task.id));
-    print_color(
+    title.push(Segment::new(crossterm::style::Color::Blue, &task.title));
+    title.push(Segment::new(
+        crossterm::style::Color::Yellow,
+        format!(" ({})", task.id),
+    ));
+    if !task.identifier.is_empty() && !task.identifier.starts_with('#') {
+        title.push(Segment::new(
+            crossterm::style::Color::Yellow,
+            format!(" {}", task.identifier),
+        ));
+    }
+    title.push(Segment::new(
         crossterm::style::Color::DarkRed,
-        &format!(" [{}]\n", api.get_project_name_from_id(task.project_id)),
-    );
+        format!(" [{project}]"),
+    ));
+    lines.push(title);
 
-    if let Some(user) = task.created_by {
-        println!("Created by {}", user.username);
+    if let Some(user) = &task.created_by {
+        lines.push(vec![Segment::plain(format!(
+            "Created by {}",
+            user.username
+        ))]);
     }
 
-    println!(
+    lines.push(vec![Segment::plain(format!(
         "Created: {} | Updated: {}",
-        time_relative(parse_datetime(&task.created).unwrap()),
-        time_relative(parse_datetime(&task.updated).unwrap())
-    );
+        parse_datetime(&task.created).map_or_else(String::new, time_relative),
+        parse_datetime(&task.updated).map_or_else(String::new, time_relative)
+    ))]);
 
     if let Some(due_date) = parse_datetime(&task.due_date) {
-        print_color(
+        lines.push(vec![Segment::new(
             if is_in_past(due_date) {
                 crossterm::style::Color::Red
             } else {
                 crossterm::style::Color::Reset
             },
-            &format!("Due {}", time_relative(due_date)),
-        );
-        println!();
+            format!("Due {}", time_relative(due_date)),
+        )]);
     }
 
     if task.priority != 0 {
-        println!("Priority: {}", task.priority);
+        lines.push(vec![Segment::plain(format!("Priority: {}", task.priority))]);
+    }
+
+    let has_subtasks = task
+        .related_tasks
+        .as_ref()
+        .is_some_and(|x| x.get("subtask").is_some_and(|x| !x.is_empty()));
+    if !task.done && (task.percent_done > 0.0 || has_subtasks) {
+        lines.push(vec![Segment::plain(format!(
+            "Progress: {}",
+            progress_bar(progress::task_progress(task, tasks), 20)
+        ))]);
     }
 
     if let (Some(start_date), Some(end_date)) = (
         parse_datetime(&task.start_date),
         parse_datetime(&task.end_date),
     ) {
-        println!("{start_date} -> {end_date}");
+        lines.push(vec![Segment::plain(format!("{start_date} -> {end_date}"))]);
     }
 
-    if let Some(labels) = task.labels {
-        print!("Labels: ");
+    if let Some(labels) = &task.labels {
+        let mut line = vec![Segment::plain("Labels: ")];
         for label in labels {
-            print_label(&label);
-            print!(" ");
+            line.push(Segment::bg(
+                hex_to_color(&label.hex_color).unwrap_or(crossterm::style::Color::Reset),
+                label.title.trim(),
+            ));
+            line.push(Segment::plain(" "));
         }
-        println!();
+        lines.push(line);
     }
 
-    if let Some(assigned) = task.assignees {
-        print!("Assigned to: ");
+    if let Some(assigned) = &task.assignees {
+        let mut line = vec![Segment::plain("Assigned to: ")];
         for assignee in assigned {
-            print!("{} ", assignee.username);
+            line.push(Segment::plain(format!("{} ", assignee.username)));
         }
+        lines.push(line);
+    }
+
+    if let Some(related) = &task.related_tasks {
+        for (kind, tasks) in related {
+            let mut line = vec![Segment::new(
+                crossterm::style::Color::Magenta,
+                format!("{}: ", Relation::repr_kind(kind)),
+            )];
+            for t in tasks {
+                line.push(Segment::new(crossterm::style::Color::Blue, &t.title));
+                line.push(Segment::new(
+                    crossterm::style::Color::Yellow,
+                    format!(" ({})", t.id),
+                ));
+                if t.done {
+                    line.push(Segment::new(crossterm::style::Color::Green, " ✓"));
+                }
+                line.push(Segment::plain(" "));
+            }
+            lines.push(line);
+        }
+    }
+
+    if task.description != "<p></p>" && !task.description.is_empty() {
+        lines.push(vec![Segment::plain("---")]);
+        let description = html2text::from_read(task.description.as_bytes(), width.max(1));
+        for line in description.lines() {
+            lines.push(vec![Segment::plain(line)]);
+        }
+    }
+
+    lines.extend(checklist_lines(&task.description));
+    lines
+}
+
+/// Print all relations of a task grouped by kind
+pub fn print_relations(task_id: isize, api: &VikunjaAPI, identifier: bool) {
+    let task = api.get_task(task_id).unwrap_or_else(|()| {
+        print_color(
+            crossterm::style::Color::Red,
+            &format!("Could not get task #{task_id}"),
+        );
         println!();
+        std::process::exit(1);
+    });
+
+    let mut related: Vec<_> = task
+        .related_tasks
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(_, tasks)| !tasks.is_empty())
+        .collect();
+
+    if related.is_empty() {
+        println!("'{}' ({}) has no relations", task.title, task.id);
+        return;
+    }
+
+    related.sort_by(|a, b| a.0.cmp(&b.0));
+    let projects = api.get_all_projects();
+
+    for (kind, tasks) in related {
+        print_color(
+            crossterm::style::Color::Magenta,
+            &format!("{}:\n", Relation::repr_kind(&kind)),
+        );
+        for t in &tasks {
+            print!("  ");
+            print_task_oneline(t, &projects, identifier);
+        }
+    }
+}
+
+fn priority_name(priority: usize) -> &'static str {
+    match priority {
+        0 => "unset",
+        1 => "low",
+        2 => "medium",
+        3 => "high",
+        4 => "urgent",
+        _ => "DO NOW",
+    }
+}
+
+/// Why a task ranks where it does in `vk next`
+fn next_reason(task: &Task) -> String {
+    let mut reasons = Vec::new();
+
+    if task.priority > 0 {
+        reasons.push(format!("priority {}", priority_name(task.priority)));
+    }
+
+    if let Some(due_date) = parse_datetime(&task.due_date) {
+        reasons.push(if is_in_past(due_date) {
+            format!("overdue since {}", time_relative(due_date))
+        } else {
+            format!("due {}", time_relative(due_date))
+        });
+    }
+
+    if task.is_favorite {
+        reasons.push("favorite".to_string());
+    }
+
+    if reasons.is_empty() {
+        "no priority or due date".to_string()
+    } else {
+        reasons.join(", ")
+    }
+}
+
+/// Print open tasks without unfinished blockers ordered by priority, due date and favorite
+pub fn print_next_tasks(
+    api: &VikunjaAPI,
+    project: Option<&ProjectID>,
+    limit: usize,
+    identifier: bool,
+) -> Result<(), String> {
+    let tasks = match project {
+        Some(p_id) if p_id.saved_filter_id().is_some() => api.get_project_tasks(p_id)?,
+        _ => api.get_all_tasks(),
+    };
+
+    let (mut ready, blocked): (Vec<Task>, Vec<Task>) = tasks
+        .into_iter()
+        .filter(|x| !x.done)
+        .filter(|x| {
+            project
+                .filter(|p| p.saved_filter_id().is_none())
+                .is_none_or(|p| x.project_id == p.0)
+        })
+        .partition(|x| x.open_blockers().is_empty());
+
+    ready.sort_by_key(|x| {
+        (
+            std::cmp::Reverse(x.priority),
+            parse_datetime(&x.due_date).map_or(i64::MAX, |x| x.timestamp()),
+            !x.is_favorite,
+            x.id,
+        )
+    });
+
+    let projects = api.get_all_projects();
+
+    for task in ready.iter().take(limit) {
+        print_task_oneline(task, &projects, identifier);
+        print_color(
+            crossterm::style::Color::DarkGrey,
+            &format!("    ↳ {}\n", next_reason(task)),
+        );
+    }
+
+    if !blocked.is_empty() {
+        print_color(
+            crossterm::style::Color::DarkGrey,
+            &format!("{} tasks are waiting on blockers\n", blocked.len()),
+        );
     }
 
-    if let Some(related) = task.related_tasks {
-        for relation in related {
+    Ok(())
+}
+
+/// Warn about open blockers of tasks about to be completed, returns whether there were any
+pub fn print_open_blockers(tasks: &[Task]) -> bool {
+    let mut warned = false;
+
+    for task in tasks {
+        let blockers = task.open_blockers();
+        if blockers.is_empty() {
+            continue;
+        }
+        warned = true;
+
+        print_color(
+            crossterm::style::Color::Yellow,
+            &format!(
+                "Warning: '{}' ({}) is still blocked by ",
+                task.title, task.id
+            ),
+        );
+        for (i, blocker) in blockers.iter().enumerate() {
+            if i > 0 {
+                print!(", ");
+            }
+            print_color(crossterm::style::Color::Blue, &blocker.title);
             print_color(
-                crossterm::style::Color::Magenta,
-                &format!("{}: ", Relation::try_parse(&relation.0).unwrap().repr()),
+                crossterm::style::Color::Yellow,
+                &format!(" ({})", blocker.id),
             );
-            for t in relation.1 {
-                // todo : add done indication
-                print_color(crossterm::style::Color::Blue, &t.title);
-                print_color(crossterm::style::Color::Yellow, &format!(" ({})", t.id));
-                print!(" ");
-            }
+        }
+        println!();
+    }
+
+    warned
+}
+
+/// Print the tasks which have no open blockers left after `done` were completed
+pub fn print_unblocked_tasks(api: &VikunjaAPI, done: &[Task], identifier: bool) {
+    let done_ids: Vec<isize> = done.iter().map(|x| x.id).collect();
+
+    let mut candidates: Vec<isize> = done
+        .iter()
+        .flat_map(|x| x.related("blocking"))
+        .filter(|x| !x.done && !done_ids.contains(&x.id))
+        .map(|x| x.id)
+        .collect();
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    let unblocked: Vec<Task> = candidates
+        .into_iter()
+        .filter_map(|id| api.get_task(id).ok())
+        .filter(|x| {
+            x.open_blockers()
+                .iter()
+                .all(|blocker| done_ids.contains(&blocker.id))
+        })
+        .collect();
+
+    if unblocked.is_empty() {
+        return;
+    }
+
+    println!("Unblocked:");
+    let projects = api.get_all_projects();
+    for task in &unblocked {
+        print_task_oneline(task, &projects, identifier);
+    }
+}
+
+/// A task with its subtasks
+struct SubtaskNode {
+    task: Task,
+    children: Vec<SubtaskNode>,
+    /// The task already appears above itself
+    cycle: bool,
+    /// There are more subtasks below the depth limit
+    truncated: bool,
+}
+
+impl SubtaskNode {
+    fn build(api: &VikunjaAPI, task: Task, depth: usize, path: &mut Vec<isize>) -> Self {
+        if path.contains(&task.id) {
+            return Self {
+                task,
+                children: Vec::new(),
+                cycle: true,
+                truncated: false,
+            };
+        }
+
+        let subtasks: Vec<isize> = task
+            .related_tasks
+            .as_ref()
+            .and_then(|x| x.get("subtask"))
+            .map(|x| x.iter().map(|t| t.id).collect())
+            .unwrap_or_default();
+
+        if depth == 0 {
+            return Self {
+                task,
+                children: Vec::new(),
+                cycle: false,
+                truncated: !subtasks.is_empty(),
+            };
+        }
+
+        path.push(task.id);
+        let children = subtasks
+            .into_iter()
+            .filter_map(|id| api.get_task(id).ok())
+            .map(|sub| Self::build(api, sub, depth - 1, path))
+            .collect();
+        path.pop();
+
+        Self {
+            task,
+            children,
+            cycle: false,
+            truncated: false,
+        }
+    }
+
+    /// Done and total count of all subtasks below this task
+    fn completion(&self) -> (usize, usize) {
+        self.children
+            .iter()
+            .filter(|x| !x.cycle)
+            .fold((0, 0), |(done, total), child| {
+                let (child_done, child_total) = child.completion();
+                (
+                    done + child_done + usize::from(child.task.done),
+                    total + child_total + 1,
+                )
+            })
+    }
+
+    /// Print the tree below `prefix`. `last` is `None` for the root task.
+    fn print(&self, prefix: &str, last: Option<bool>) {
+        let task = &self.task;
+
+        let (guide, indent) = match last {
+            None => ("", ""),
+            Some(false) => ("├── ", "│   "),
+            Some(true) => ("└── ", "    "),
+        };
+
+        print!("{prefix}{guide}");
+        if task.done {
+            print_color(crossterm::style::Color::Green, "✓ ");
+        } else {
+            print!("○ ");
+        }
+        print_color(crossterm::style::Color::Blue, &task.title);
+        print_color(crossterm::style::Color::Yellow, &format!(" ({})", task.id));
+
+        if self.cycle {
+            print_color(crossterm::style::Color::Red, " (cycle)");
             println!();
+            return;
+        }
+
+        if let Some(due_date) = parse_datetime(&task.due_date).filter(|_| !task.done) {
+            print_color(
+                if is_in_past(due_date) {
+                    crossterm::style::Color::Red
+                } else {
+                    crossterm::style::Color::Reset
+                },
+                &format!(" due {}", time_relative(due_date)),
+            );
+        }
+
+        for label in task.labels.iter().flatten() {
+            print!(" ");
+            print_label(label);
+        }
+
+        let (done, total) = self.completion();
+        if total > 0 {
+            print_color(
+                crossterm::style::Color::DarkGrey,
+                &format!(" {done}/{total} subtasks done"),
+            );
+        }
+
+        if self.truncated {
+            print_color(crossterm::style::Color::DarkGrey, " …");
+        }
+
+        println!();
+
+        let prefix = format!("{prefix}{indent}");
+
+        for (i, child) in self.children.iter().enumerate() {
+            child.print(&prefix, Some(i == self.children.len() - 1));
         }
     }
+}
 
-    if task.description != "<p></p>" && !task.description.is_empty() {
-        println!("---\n{}", format_html_to_terminal(&task.description));
+/// Print a task with all its subtasks as tree up to `depth` levels deep
+pub fn print_task_tree(task_id: isize, depth: usize, api: &VikunjaAPI) {
+    let task = api.get_task(task_id).unwrap_or_else(|()| {
+        print_color(
+            crossterm::style::Color::Red,
+            &format!("Could not get task #{task_id}"),
+        );
+        println!();
+        std::process::exit(1);
+    });
+
+    SubtaskNode::build(api, task, depth, &mut Vec::new()).print("", None);
+}
+
+/// Print the numbered checklist items of a task with their progress
+pub fn print_checklist(description: &str) {
+    print_lines(&checklist_lines(description));
+}
+
+fn checklist_lines(description: &str) -> Vec<Vec<Segment>> {
+    let items = checklist::parse(description);
+
+    if items.is_empty() {
+        return Vec::new();
+    }
+
+    let done = items.iter().filter(|x| x.checked).count();
+    let mut lines = vec![
+        vec![Segment::plain("---")],
+        vec![Segment::plain(format!(
+            "Checklist {done}/{} done",
+            items.len()
+        ))],
+    ];
+
+    for (i, item) in items.iter().enumerate() {
+        lines.push(vec![
+            if item.checked {
+                Segment::new(
+                    crossterm::style::Color::Green,
+                    format!("{:>3}. [✓] ", i + 1),
+                )
+            } else {
+                Segment::plain(format!("{:>3}. [ ] ", i + 1))
+            },
+            Segment::plain(&item.text),
+        ]);
     }
 
-    // pub percent_done: f64,
+    lines
 }
 
 pub fn print_comment(comment: &Comment) {
+    print_color(
+        crossterm::style::Color::Yellow,
+        &format!("({}) ", comment.id),
+    );
     print_color(crossterm::style::Color::Blue, &comment.author.username);
     print!(
-        " ({}): ",
-        time_relative(parse_datetime(&comment.created).unwrap())
+        " ({}",
+        parse_datetime(&comment.created).map_or_else(String::new, time_relative)
     );
+    if comment.updated != comment.created {
+        print_color(crossterm::style::Color::DarkGrey, ", edited");
+    }
+    print!("): ");
     println!();
     print!("{}", format_html_to_terminal(&comment.comment));
     println!();
@@ -0,0 +1,68 @@
+use crossterm::style::Color;
+
+use crate::{
+    api::{ProjectID, Right, VikunjaAPI},
+    ui::print_color,
+};
+
+pub fn list_teams(api: &VikunjaAPI) {
+    for team in api.get_teams() {
+        print_color(Color::Blue, &team.name);
+        print_color(Color::Yellow, &format!(" [{}]", team.id));
+        println!();
+
+        if !team.description.is_empty() {
+            println!("  {}", team.description);
+        }
+
+        for member in team.members.iter().flatten() {
+            print!("  - {}", member.user.username);
+            if member.admin {
+                print_color(Color::DarkGrey, " (admin)");
+            }
+            println!();
+        }
+    }
+}
+
+/// Print the owner of a project and everyone it is shared with
+pub fn print_project_members(api: &VikunjaAPI, project: &ProjectID) {
+    let prj = api.get_project(project);
+
+    if let Some(owner) = &prj.owner {
+        print!("{} ", owner.username);
+        print_color(Color::DarkGrey, "owner");
+        println!();
+    }
+
+    for shared in api.get_project_users(project) {
+        print!("{} ", shared.user.username);
+        print_color(Color::DarkGrey, &Right::from_api(shared.right).repr());
+        println!();
+    }
+
+    for shared in api.get_project_teams(project) {
+        print_color(Color::Blue, &format!("Team {} ", shared.team.name));
+        print_color(Color::DarkGrey, &Right::from_api(shared.right).repr());
+        println!();
+
+        for member in shared.team.members.iter().flatten() {
+            println!("  - {}", member.user.username);
+        }
+    }
+}
+
+pub fn print_link_shares(api: &VikunjaAPI, project: &ProjectID) {
+    for share in api.get_link_shares(project) {
+        print_color(Color::Yellow, &format!("[{}] ", share.id));
+        if !share.name.is_empty() {
+            print_color(Color::Blue, &format!("{} ", share.name));
+        }
+        print_color(Color::DarkGrey, &Right::from_api(share.right).repr());
+        if share.has_password() {
+            print_color(Color::DarkGrey, ", password");
+        }
+        println!();
+        println!("  {}", api.link_share_url(&share));
+    }
+}
@@ -0,0 +1,36 @@
+use crossterm::style::Color;
+
+use crate::{
+    trash::{TrashEntry, TrashItem},
+    ui::{parse_datetime, plural, print_color, time_relative},
+};
+
+pub fn print_trash(entries: &[TrashEntry]) {
+    for entry in entries {
+        print_color(Color::Yellow, &format!("({}) ", entry.id));
+
+        match &entry.item {
+            TrashItem::Task(snapshot) => {
+                print_color(Color::Blue, &snapshot.task.title);
+                print!(" task #{}", snapshot.task.id);
+            }
+            TrashItem::Project(snapshot) => {
+                print_color(Color::Blue, &snapshot.project.title);
+                print!(
+                    " project [{}] with {}",
+                    snapshot.project.id,
+                    plural(snapshot.task_count(), "task")
+                );
+            }
+        }
+
+        if let Some(deleted) = parse_datetime(&entry.deleted) {
+            print_color(
+                Color::DarkGrey,
+                &format!(" deleted {}", time_relative(deleted)),
+            );
+        }
+
+        println!();
+    }
+}
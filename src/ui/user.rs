@@ -0,0 +1,113 @@
+use crossterm::style::Color;
+
+use crate::{
+    api::{TokenKind, VikunjaAPI},
+    ui::{print_color, time_relative},
+};
+
+/// User settings vk knows about, by their name and key in the API
+pub const SETTINGS: [(&str, &str); 7] = [
+    ("name", "name"),
+    ("default_project", "default_project_id"),
+    ("week_start", "week_start"),
+    ("timezone", "timezone"),
+    ("language", "language"),
+    ("email_reminders", "email_reminders_enabled"),
+    ("overdue_reminders", "overdue_tasks_reminders_enabled"),
+];
+
+pub const WEEKDAYS: [&str; 7] = [
+    "sunday",
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+];
+
+pub fn print_whoami(api: &VikunjaAPI, token: &str) {
+    let token = TokenKind::parse(token);
+
+    match api.current_user() {
+        Ok(user) => {
+            print_color(Color::Blue, &user.username);
+            if !user.name.is_empty() {
+                print!(" ({})", user.name);
+            }
+            println!();
+        }
+        Err(msg) if matches!(token, TokenKind::LinkShare { .. }) => {
+            println!("Link share ({msg})");
+        }
+        Err(msg) => {
+            print_color(Color::Red, &msg);
+            println!();
+        }
+    }
+
+    print!("Host: {}", api.host());
+    if let Some(info) = api.info() {
+        print_color(Color::DarkGrey, &format!(" (Vikunja {})", info.version));
+    }
+    println!();
+
+    print!("Token: {}", token.repr());
+    if let Some(expires) = token.expires() {
+        let expiry = time_relative(expires);
+        if expires < chrono::Utc::now() {
+            print_color(Color::Red, &format!(", expired {expiry}"));
+        } else {
+            print!(", expires {expiry}");
+        }
+    }
+    println!();
+}
+
+/// Human readable value of the setting `key`
+fn setting_value(api: &VikunjaAPI, key: &str, value: &serde_json::Value) -> Result<String, String> {
+    Ok(match (key, value) {
+        ("default_project", serde_json::Value::Number(id)) => {
+            let id = id.as_i64().unwrap_or_default() as isize;
+            if id > 0 {
+                let name = api
+                    .get_project_name_from_id(id)
+                    .map_err(|msg| format!("Could not get the default project #{id}: {msg}"))?;
+                format!("{name} [{id}]")
+            } else {
+                String::from("none")
+            }
+        }
+        ("week_start", serde_json::Value::Number(day)) => WEEKDAYS
+            .get(day.as_u64().unwrap_or_default() as usize)
+            .map_or_else(|| day.to_string(), |x| (*x).to_string()),
+        (_, serde_json::Value::String(val)) => val.clone(),
+        (_, val) => val.to_string(),
+    })
+}
+
+/// Print the user setting `key` or all settings vk knows about
+pub fn print_settings(
+    api: &VikunjaAPI,
+    settings: &serde_json::Value,
+    key: Option<&str>,
+) -> Result<(), String> {
+    for (name, api_key) in SETTINGS {
+        if key.is_some_and(|x| x != name) {
+            continue;
+        }
+
+        let value = settings
+            .get(api_key)
+            .map_or_else(|| Ok(String::new()), |x| setting_value(api, name, x))?;
+
+        if key.is_some() {
+            println!("{value}");
+        } else {
+            print_color(Color::Blue, name);
+            println!(": {value}");
+        }
+    }
+
+    Ok(())
+}